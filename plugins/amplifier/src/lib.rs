@@ -3,8 +3,8 @@ use bevy::{color::palettes, ecs::world::Command, prelude::*};
 use game_loop::InGame;
 use merchandise::{MerchAppExt, Merchandise, Money};
 use tiles::{
-    lasers::{Amplification, Position},
-    Owner, Tile, TileParameters, TilePlugin,
+    lasers::{Amplification, Consumption, Direction, Position},
+    tile_material, Owner, Tile, TileParameters, TilePlugin,
 };
 
 pub struct AmplifierPlugin;
@@ -21,6 +21,10 @@ impl Plugin for AmplifierPlugin {
 pub struct AmplifierTile;
 
 impl Tile for AmplifierTile {
+    const MAX_HEALTH: Option<usize> = Some(2);
+    const OWNER_IMMUNE: bool = true;
+    const TEXTURE: Option<&'static str> = Some("tiles/amplifier.png");
+
     fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
         AmplifierSpawn { position, player }
     }
@@ -31,22 +35,32 @@ impl Tile for AmplifierTile {
 
     fn activate(
         &self,
-        _entity: Entity,
+        entity: Entity,
         parameters: TileParameters,
         _shooter: Option<Entity>,
     ) -> impl Command {
         AmplifierActivate {
+            tile: entity,
             position: parameters.position,
+            strength: *parameters.level as usize,
+            vulnerable: Self::vulnerable(&parameters),
         }
     }
+
+    // Every laser from another player amplified by the tile wears it down
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
 }
 
 impl Merchandise for AmplifierTile {
+    const KEY: &'static str = "amplifier";
     const PRICE: Money = Money::new(3);
     const NAME: &'static str = "Amplifier Tower";
+    const UPGRADABLE: bool = true;
 
     fn material(asset_server: &AssetServer) -> ColorMaterial {
-        let mut base = <Self as Tile>::material(asset_server);
+        let mut base = tile_material::<Self>(asset_server, 0.);
         base.color.set_alpha(0.6);
         base
     }
@@ -71,13 +85,17 @@ impl Command for AmplifierSpawn {
 }
 
 pub struct AmplifierActivate {
+    tile: Entity,
     position: Position,
+    strength: usize,
+    vulnerable: Vec<Direction>,
 }
 
 impl Command for AmplifierActivate {
     fn apply(self, world: &mut World) {
-        world.spawn((Amplification::new(1), self.position.clone()));
+        world.spawn((
+            Amplification::new(self.strength),
+            Consumption::passing_bundle(self.tile, self.vulnerable, self.position.clone()),
+        ));
     }
 }
-
-// on-hit for amplifier later
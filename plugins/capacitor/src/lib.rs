@@ -0,0 +1,278 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    color::palettes,
+    ecs::{system::SystemState, world::Command},
+    prelude::*,
+    sprite::MaterialMesh2dBundle,
+};
+
+use game_loop::InGame;
+use merchandise::{MerchAppExt, Merchandise, Money};
+use popups::PopupEvent;
+use shop::JustPurchased;
+use tilemap::TilemapLayout;
+use tiles::{
+    lasers::{Consumption, Direction, Laser, LaserKind, LaserStrength, Position, Shooter},
+    tile_material, DamageTile, Level, Owner, Tile, TileParameters, TilePlugin,
+};
+
+pub struct CapacitorPlugin;
+
+impl Plugin for CapacitorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Charge>()
+            .add_plugins(TilePlugin::<CapacitorTile>::default())
+            .add_systems(Update, Self::update_marker);
+        app.define_merchandise::<CapacitorTile>();
+    }
+}
+
+impl CapacitorPlugin {
+    fn update_marker(
+        tiles: Query<&Direction, (With<CapacitorTile>, Changed<Direction>)>,
+        mut markers: Query<(&Parent, &mut Transform), With<CapacitorMarker>>,
+    ) {
+        for (parent, mut transform) in &mut markers {
+            if let Ok(direction) = tiles.get(**parent) {
+                *transform = match *direction {
+                    Direction::North => Transform::IDENTITY,
+                    Direction::Northwest => {
+                        Transform::from_rotation(Quat::from_rotation_z(PI / 3.))
+                    }
+                    Direction::Southwest => {
+                        Transform::from_rotation(Quat::from_rotation_z(2. * PI / 3.))
+                    }
+                    Direction::South => Transform::from_rotation(Quat::from_rotation_z(PI)),
+                    Direction::Southeast => {
+                        Transform::from_rotation(Quat::from_rotation_z(4. * PI / 3.))
+                    }
+                    Direction::Northeast => {
+                        Transform::from_rotation(Quat::from_rotation_z(5. * PI / 3.))
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct CapacitorTile;
+
+impl CapacitorTile {
+    // Charge the capacitor can hold per level, anything beyond that damages it
+    pub const CAPACITY: usize = 3;
+}
+
+impl Tile for CapacitorTile {
+    const MAX_HEALTH: Option<usize> = Some(3);
+    const TEXTURE: Option<&'static str> = Some("tiles/capacitor.png");
+
+    fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
+        CapacitorSpawn { position, player }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        Color::Srgba(palettes::css::GOLD).into()
+    }
+
+    fn activate(
+        &self,
+        tile: Entity,
+        parameters: TileParameters,
+        shooter: Option<Entity>,
+    ) -> impl Command {
+        CapacitorActivate {
+            tile,
+            position: parameters.position,
+            direction: parameters
+                .direction
+                .unwrap_or_else(|| panic!("Capacitor needs a direction")),
+            shooter: shooter.unwrap_or_else(|| panic!("Capacitor needs to have a owner to shoot")),
+            vulnerable: Self::vulnerable(&parameters),
+        }
+    }
+
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
+
+    // Hits are stored as charge instead of damaging the capacitor until it is full
+    fn on_hit(
+        &self,
+        entity: Entity,
+        strength: usize,
+        _shooter: Entity,
+        _kind: LaserKind,
+    ) -> Option<impl Command> {
+        Some(CapacitorOnHit {
+            tile: entity,
+            strength,
+        })
+    }
+}
+
+impl Merchandise for CapacitorTile {
+    const KEY: &'static str = "capacitor";
+    const PRICE: Money = Money::new(8);
+    const NAME: &'static str = "Capacitor";
+    const UPGRADABLE: bool = true;
+
+    fn material(asset_server: &AssetServer) -> ColorMaterial {
+        let mut base = tile_material::<Self>(asset_server, 0.);
+        base.color.set_alpha(0.6);
+        base
+    }
+}
+
+// Laser strength absorbed by a capacitor, fired as a single beam on the next act phase
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Deref, DerefMut, Reflect)]
+pub struct Charge(usize);
+
+pub struct CapacitorSpawn {
+    position: Position,
+    player: Entity,
+}
+
+impl Command for CapacitorSpawn {
+    #[allow(clippy::type_complexity)]
+    fn apply(self, world: &mut World) {
+        let mut system_state: SystemState<(
+            Query<&TilemapLayout>,
+            ResMut<Assets<Mesh>>,
+            ResMut<Assets<ColorMaterial>>,
+        )> = SystemState::new(world);
+
+        let (layout, mut meshes, mut materials) = system_state.get_mut(world);
+
+        let Ok(translation) = layout
+            .get_single()
+            .map(|layout| layout.hex_to_world_pos(*self.position).extend(11.))
+        else {
+            info!("Did not get the single tilemap layout for the game");
+            return;
+        };
+
+        let plates = meshes.add(Rectangle::new(30., 12.));
+        let black = materials.add(Color::BLACK);
+        let triangle = meshes.add(Triangle2d::new(
+            Vec2::new(-5., 10.),
+            Vec2::new(5., 10.),
+            Vec2::new(0., 35.),
+        ));
+        let white = materials.add(Color::WHITE);
+
+        if let Some(game) = world.get::<InGame>(self.player) {
+            world
+                .spawn((
+                    CapacitorTile,
+                    Charge::default(),
+                    self.position,
+                    Direction::default(),
+                    Owner::new(self.player),
+                    game.clone(),
+                    Transform::from_translation(translation),
+                    GlobalTransform::from_translation(translation),
+                    JustPurchased,
+                ))
+                .with_children(|builder| {
+                    builder.spawn(MaterialMesh2dBundle {
+                        mesh: plates.into(),
+                        material: black,
+                        transform: Transform::default(),
+                        ..default()
+                    });
+                    builder.spawn((
+                        CapacitorMarker,
+                        MaterialMesh2dBundle {
+                            mesh: triangle.into(),
+                            material: white,
+                            transform: Transform::default(),
+                            ..default()
+                        },
+                    ));
+                });
+        }
+    }
+}
+
+pub struct CapacitorActivate {
+    tile: Entity,
+    position: Position,
+    direction: Direction,
+    shooter: Entity,
+    vulnerable: Vec<Direction>,
+}
+
+impl Command for CapacitorActivate {
+    fn apply(self, world: &mut World) {
+        world.spawn(Consumption::bundle(
+            self.tile,
+            self.vulnerable,
+            self.position,
+        ));
+
+        // Charge gathered during the last act phase is released all at once
+        let Some(mut charge) = world.get_mut::<Charge>(self.tile) else {
+            return;
+        };
+        let strength = std::mem::take(&mut **charge);
+        if strength == 0 {
+            return;
+        }
+        info!("Capacitor {:?} discharging {}", self.tile, strength);
+        world.spawn((
+            Laser,
+            self.position,
+            self.direction,
+            Shooter::new(self.shooter),
+            LaserStrength::new(strength),
+        ));
+    }
+}
+
+pub struct CapacitorOnHit {
+    tile: Entity,
+    strength: usize,
+}
+
+impl Command for CapacitorOnHit {
+    fn apply(self, world: &mut World) {
+        let level = world.get::<Level>(self.tile).copied().unwrap_or_default();
+        let capacity = CapacitorTile::CAPACITY * *level as usize;
+        let Some(mut charge) = world.get_mut::<Charge>(self.tile) else {
+            return;
+        };
+        let stored = self.strength.min(capacity.saturating_sub(**charge));
+        **charge += stored;
+
+        if stored > 0 {
+            if let Some(tile_entity) = world
+                .get::<Position>(self.tile)
+                .copied()
+                .and_then(|position| position.get_tile_entity(world))
+            {
+                world.trigger_targets(
+                    PopupEvent {
+                        text: format!("+{} charge", stored),
+                    },
+                    tile_entity,
+                );
+            }
+        }
+
+        if stored < self.strength {
+            DamageTile {
+                tile: self.tile,
+                strength: self.strength - stored,
+            }
+            .apply(world);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct CapacitorMarker;
@@ -0,0 +1,537 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, utils::HashMap};
+use sickle_ui::prelude::RadioGroup;
+
+use game_loop::GamePhase;
+use merchandise::{
+    ActionHistory, HistoryAction, MerchRegistry, Money, Orientation, Purchase, Redo, Reorient, Undo,
+};
+use shop::{
+    ControllingPlayer, JustPurchased, SelectedMerch, SelectedTower, ShopMerchOption, ShopSystems,
+    TileAdjuster,
+};
+use territory_visuals::TerritoryDisplay;
+use tilemap::{TargetedTile, Tilemap, TilemapEntities};
+use tiles::{
+    lasers::{Direction, Position, Rotation, YReflection},
+    Owner,
+};
+
+// Keyboard and gamepad actions for moving the hex cursor, picking merch, orienting towers and
+// confirming purchases, all driven through the same resources and events as the mouse
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<InputBindings>()
+            .init_resource::<InputBindings>()
+            .add_event::<ControlActionEvent>()
+            .add_systems(
+                Update,
+                (
+                    Self::read_inputs,
+                    Self::move_cursor,
+                    Self::cycle_merch,
+                    Self::rotate_tower,
+                    Self::confirm_purchase,
+                    Self::undo_redo,
+                    Self::toggle_territories,
+                )
+                    .chain()
+                    .before(ShopSystems)
+                    .in_set(ControlSystems),
+            );
+    }
+}
+
+impl ControlsPlugin {
+    #[allow(clippy::too_many_arguments)]
+    fn read_inputs(
+        bindings: Res<InputBindings>,
+        keys: Res<ButtonInput<KeyCode>>,
+        buttons: Res<ButtonInput<GamepadButton>>,
+        axes: Res<Axis<GamepadAxis>>,
+        gamepads: Res<Gamepads>,
+        time: Res<Time>,
+        mut repeat: Local<Option<Timer>>,
+        mut actions: EventWriter<ControlActionEvent>,
+    ) {
+        for action in ControlAction::ALL {
+            let pressed = bindings.get(action).any(|binding| match binding {
+                InputBinding::Key(key) => keys.just_pressed(*key),
+                InputBinding::Gamepad(button_type) => gamepads
+                    .iter()
+                    .any(|gamepad| buttons.just_pressed(GamepadButton::new(gamepad, *button_type))),
+            });
+            if pressed {
+                actions.send(ControlActionEvent(action));
+            }
+        }
+
+        // The stick moves the cursor once when tilted and then repeats while it is held
+        let stick = gamepads
+            .iter()
+            .map(|gamepad| {
+                Vec2::new(
+                    axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+                        .unwrap_or_default(),
+                    axes.get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY))
+                        .unwrap_or_default(),
+                )
+            })
+            .find(|stick| stick.length() >= bindings.stick_threshold);
+        let Some(stick) = stick else {
+            *repeat = None;
+            return;
+        };
+        let fire = match repeat.as_mut() {
+            Some(timer) => timer.tick(time.delta()).just_finished(),
+            None => {
+                *repeat = Some(Timer::new(bindings.stick_repeat, TimerMode::Repeating));
+                true
+            }
+        };
+        if fire {
+            let action = if stick.x.abs() > stick.y.abs() {
+                if stick.x > 0. {
+                    ControlAction::CursorRight
+                } else {
+                    ControlAction::CursorLeft
+                }
+            } else if stick.y > 0. {
+                ControlAction::CursorUp
+            } else {
+                ControlAction::CursorDown
+            };
+            actions.send(ControlActionEvent(action));
+        }
+    }
+
+    fn move_cursor(
+        mut commands: Commands,
+        mut actions: EventReader<ControlActionEvent>,
+        targeted_tile: Option<ResMut<TargetedTile>>,
+        tilemaps: Query<(Entity, &TilemapEntities), With<Tilemap>>,
+        tiles: Query<&tilemap::Tile>,
+    ) {
+        let Ok((tilemap, tilemap_entities)) = tilemaps.get_single() else {
+            return;
+        };
+        let mut hex = targeted_tile
+            .as_deref()
+            .and_then(|targeted| tiles.get(targeted.tile).ok())
+            .map(|tile| **tile);
+        let mut moved = false;
+
+        for ControlActionEvent(action) in actions.read() {
+            // Only cursor actions move or place the cursor
+            let Some(direction) = action.cursor_direction(hex.map_or(0, |hex| hex.x)) else {
+                continue;
+            };
+            let Some(current) = hex else {
+                // The first cursor movement without a target starts from the center of the map
+                hex = Some(Default::default());
+                moved = true;
+                continue;
+            };
+            let next = current.neighbor(direction.as_hex());
+            if tilemap_entities.tiles.contains_key(&next) {
+                hex = Some(next);
+                moved = true;
+            }
+        }
+
+        let Some(tile) = hex
+            .filter(|_| moved)
+            .and_then(|hex| tilemap_entities.tiles.get(&hex).copied())
+        else {
+            return;
+        };
+        if let Some(mut targeted_tile) = targeted_tile {
+            targeted_tile.tile = tile;
+            targeted_tile.tilemap = tilemap;
+        } else {
+            commands.insert_resource(TargetedTile { tile, tilemap });
+        }
+    }
+
+    // Cycling goes through the shop's radio group so that the UI stays in sync
+    fn cycle_merch(
+        mut actions: EventReader<ControlActionEvent>,
+        mut radio_groups: Query<&mut RadioGroup, With<ShopMerchOption>>,
+        registry: Res<MerchRegistry>,
+    ) {
+        let Ok(mut radio_group) = radio_groups.get_single_mut() else {
+            return;
+        };
+        let options = registry.sorted().len();
+        if options == 0 {
+            return;
+        }
+
+        for ControlActionEvent(action) in actions.read() {
+            let offset = match action {
+                ControlAction::NextMerch => 1,
+                ControlAction::PreviousMerch => options - 1,
+                _ => continue,
+            };
+            let next = radio_group
+                .selected()
+                .map_or(0, |selected| (selected + offset) % options);
+            radio_group.select(next);
+        }
+    }
+
+    // Rotating moves the tile adjuster of the targeted tower, so towers bought this turn are
+    // updated by the shop and older towers are reoriented for a fee
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    fn rotate_tower(
+        mut actions: EventReader<ControlActionEvent>,
+        targeted_tile: Option<Res<TargetedTile>>,
+        selected_tower: Option<Res<SelectedTower>>,
+        tiles: Query<&tilemap::Tile>,
+        towers: Query<(
+            Entity,
+            &Position,
+            &Owner,
+            &Children,
+            Option<&Direction>,
+            Option<&Rotation>,
+            Option<&YReflection>,
+            Has<JustPurchased>,
+        )>,
+        mut markers: Query<&mut Transform, With<TileAdjuster>>,
+        mut histories: Query<&mut ActionHistory>,
+        controlling_player: Option<Res<ControllingPlayer>>,
+        mut reorients: EventWriter<Reorient>,
+    ) {
+        for ControlActionEvent(action) in actions.read() {
+            let clockwise = match action {
+                ControlAction::RotateClockwise => true,
+                ControlAction::RotateCounterclockwise => false,
+                _ => continue,
+            };
+            let Some(targeted_tile) = targeted_tile.as_deref() else {
+                continue;
+            };
+            let Ok(hex) = tiles.get(targeted_tile.tile) else {
+                continue;
+            };
+            let Some((tower, _, owner, children, direction, rotation, tilt, just_purchased)) =
+                towers.iter().find(|(_, position, ..)| ***position == **hex)
+            else {
+                continue;
+            };
+            let selected = selected_tower
+                .as_deref()
+                .is_some_and(|selected| selected.tower == tower);
+            if !just_purchased && !selected {
+                continue;
+            }
+            let Some(marker) = children.iter().find(|child| markers.contains(**child)) else {
+                continue;
+            };
+            let Ok(mut marker) = markers.get_mut(*marker) else {
+                continue;
+            };
+
+            let to = if let (None, None, Some(tilt)) = (direction, rotation, tilt) {
+                // Tilted towers flip between their two settings whichever way they are rotated
+                let next = tilt.toggled();
+                marker.translation = TileAdjuster::tilt_translation(next);
+                Orientation {
+                    tilt: Some(next),
+                    ..default()
+                }
+            } else {
+                let current = TileAdjuster::to_direction(marker.translation);
+                let next = if clockwise {
+                    current.clockwise(1)
+                } else {
+                    current.counterclockwise(1)
+                };
+                marker.translation = TileAdjuster::translation(next);
+                Orientation {
+                    direction: direction.map(|_| next),
+                    rotation: rotation.map(|_| TileAdjuster::to_rotation(marker.translation)),
+                    tilt: tilt.map(|_| TileAdjuster::to_tilt(marker.translation)),
+                }
+            };
+
+            if just_purchased {
+                if let Ok(mut history) = histories.get_mut(**owner) {
+                    history.record(HistoryAction::Reorient {
+                        tower,
+                        from: Orientation::new(direction, rotation, tilt),
+                        to,
+                        fee: Money::default(),
+                    });
+                }
+            } else {
+                let Some(player) = controlling_player.as_deref() else {
+                    continue;
+                };
+                reorients.send(Reorient {
+                    player: **player,
+                    tile: targeted_tile.tile,
+                    direction: to.direction,
+                    rotation: to.rotation,
+                    tilt: to.tilt,
+                });
+            }
+        }
+    }
+
+    fn confirm_purchase(
+        mut actions: EventReader<ControlActionEvent>,
+        mut purchases: EventWriter<Purchase>,
+        games: Query<&GamePhase>,
+        selected_merch: Option<Res<SelectedMerch>>,
+        targeted_tile: Option<Res<TargetedTile>>,
+        controlling_player: Option<Res<ControllingPlayer>>,
+    ) {
+        let confirmed = actions
+            .read()
+            .any(|ControlActionEvent(action)| matches!(action, ControlAction::Confirm));
+        if !confirmed
+            || !games
+                .get_single()
+                .is_ok_and(|phase| matches!(phase, GamePhase::Choose))
+        {
+            return;
+        }
+
+        if let (Some(merch), Some(targeted_tile), Some(player)) = (
+            selected_merch.as_deref(),
+            targeted_tile.as_deref(),
+            controlling_player.as_deref(),
+        ) {
+            info!("Sent purchase for merch {:?} from controls", **merch);
+            purchases.send(Purchase::new(
+                **player,
+                (**merch).clone(),
+                targeted_tile.tile,
+            ));
+        }
+    }
+
+    fn undo_redo(
+        mut actions: EventReader<ControlActionEvent>,
+        mut undos: EventWriter<Undo>,
+        mut redos: EventWriter<Redo>,
+        controlling_player: Option<Res<ControllingPlayer>>,
+    ) {
+        let Some(player) = controlling_player.as_deref() else {
+            actions.clear();
+            return;
+        };
+        for ControlActionEvent(action) in actions.read() {
+            match action {
+                ControlAction::Undo => {
+                    undos.send(Undo { player: **player });
+                }
+                ControlAction::Redo => {
+                    redos.send(Redo { player: **player });
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn toggle_territories(
+        mut actions: EventReader<ControlActionEvent>,
+        mut display: ResMut<TerritoryDisplay>,
+    ) {
+        for ControlActionEvent(action) in actions.read() {
+            if let ControlAction::ToggleTerritories = action {
+                display.toggle();
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(SystemSet)]
+pub struct ControlSystems;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+pub enum ControlAction {
+    CursorUp,
+    CursorDown,
+    CursorLeft,
+    CursorRight,
+    NextMerch,
+    PreviousMerch,
+    RotateClockwise,
+    RotateCounterclockwise,
+    Confirm,
+    Undo,
+    Redo,
+    ToggleTerritories,
+}
+
+impl ControlAction {
+    pub const ALL: [Self; 12] = [
+        Self::CursorUp,
+        Self::CursorDown,
+        Self::CursorLeft,
+        Self::CursorRight,
+        Self::NextMerch,
+        Self::PreviousMerch,
+        Self::RotateClockwise,
+        Self::RotateCounterclockwise,
+        Self::Confirm,
+        Self::Undo,
+        Self::Redo,
+        Self::ToggleTerritories,
+    ];
+
+    // Flat hexes have no straight left or right neighbor, so sideways movement zigzags between
+    // the upper and lower neighbors to stay on the same row
+    fn cursor_direction(&self, column: i32) -> Option<Direction> {
+        let even = column.rem_euclid(2) == 0;
+        match self {
+            Self::CursorUp => Some(Direction::North),
+            Self::CursorDown => Some(Direction::South),
+            Self::CursorLeft if even => Some(Direction::Northwest),
+            Self::CursorLeft => Some(Direction::Southwest),
+            Self::CursorRight if even => Some(Direction::Northeast),
+            Self::CursorRight => Some(Direction::Southeast),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Event, Deref)]
+pub struct ControlActionEvent(pub ControlAction);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+pub enum InputBinding {
+    Key(KeyCode),
+    Gamepad(GamepadButtonType),
+}
+
+// Every action can have any number of bindings, which can be changed at runtime
+#[derive(Clone, Debug)]
+#[derive(Resource, Reflect)]
+pub struct InputBindings {
+    bindings: HashMap<ControlAction, Vec<InputBinding>>,
+    pub stick_threshold: f32,
+    pub stick_repeat: Duration,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use GamepadButtonType as Button;
+        use InputBinding::{Gamepad, Key};
+
+        let bindings = [
+            (
+                ControlAction::CursorUp,
+                vec![
+                    Key(KeyCode::ArrowUp),
+                    Key(KeyCode::KeyW),
+                    Gamepad(Button::DPadUp),
+                ],
+            ),
+            (
+                ControlAction::CursorDown,
+                vec![
+                    Key(KeyCode::ArrowDown),
+                    Key(KeyCode::KeyS),
+                    Gamepad(Button::DPadDown),
+                ],
+            ),
+            (
+                ControlAction::CursorLeft,
+                vec![
+                    Key(KeyCode::ArrowLeft),
+                    Key(KeyCode::KeyA),
+                    Gamepad(Button::DPadLeft),
+                ],
+            ),
+            (
+                ControlAction::CursorRight,
+                vec![
+                    Key(KeyCode::ArrowRight),
+                    Key(KeyCode::KeyD),
+                    Gamepad(Button::DPadRight),
+                ],
+            ),
+            (
+                ControlAction::NextMerch,
+                vec![Key(KeyCode::KeyX), Gamepad(Button::RightTrigger)],
+            ),
+            (
+                ControlAction::PreviousMerch,
+                vec![Key(KeyCode::KeyZ), Gamepad(Button::LeftTrigger)],
+            ),
+            (
+                ControlAction::RotateClockwise,
+                vec![Key(KeyCode::KeyE), Gamepad(Button::East)],
+            ),
+            (
+                ControlAction::RotateCounterclockwise,
+                vec![Key(KeyCode::KeyQ), Gamepad(Button::West)],
+            ),
+            (
+                ControlAction::Confirm,
+                vec![
+                    Key(KeyCode::Enter),
+                    Key(KeyCode::NumpadEnter),
+                    Gamepad(Button::South),
+                ],
+            ),
+            (
+                ControlAction::Undo,
+                vec![
+                    Key(KeyCode::KeyU),
+                    Key(KeyCode::Backspace),
+                    Gamepad(Button::Select),
+                ],
+            ),
+            (
+                ControlAction::Redo,
+                vec![Key(KeyCode::KeyY), Gamepad(Button::Start)],
+            ),
+            (
+                ControlAction::ToggleTerritories,
+                vec![Key(KeyCode::KeyT), Gamepad(Button::North)],
+            ),
+        ];
+
+        InputBindings {
+            bindings: bindings.into_iter().collect(),
+            stick_threshold: 0.5,
+            stick_repeat: Duration::from_millis(200),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: ControlAction) -> impl Iterator<Item = &InputBinding> {
+        self.bindings.get(&action).into_iter().flatten()
+    }
+
+    pub fn bind(&mut self, action: ControlAction, binding: InputBinding) {
+        let bindings = self.bindings.entry(action).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn unbind(&mut self, action: ControlAction, binding: InputBinding) {
+        if let Some(bindings) = self.bindings.get_mut(&action) {
+            bindings.retain(|bound| *bound != binding);
+        }
+    }
+
+    // Replaces every binding of an action
+    pub fn rebind(&mut self, action: ControlAction, bindings: Vec<InputBinding>) {
+        self.bindings.insert(action, bindings);
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use game_loop::{GameLoopSystems, GamePhase, GamePlayers};
+use merchandise::{MerchSystems, Money, MoneyChangeReason, MoneyChanged, Shopper, TowerSold};
+use tiles::Territory;
+
+pub struct EconomyPlugin;
+
+impl Plugin for EconomyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<EconomyRules>().add_systems(
+            Update,
+            (Self::pay_income, Self::pay_refunds)
+                .chain()
+                .after(GameLoopSystems)
+                .after(MerchSystems)
+                .in_set(EconomySystems),
+        );
+    }
+}
+
+impl EconomyPlugin {
+    // Income and interest are paid out whenever a new choose phase begins
+    fn pay_income(
+        games: Query<(Ref<GamePhase>, &GamePlayers, Option<&EconomyRules>)>,
+        mut shoppers: Query<(&mut Money, Option<&Territory>), With<Shopper>>,
+        mut money_changes: EventWriter<MoneyChanged>,
+    ) {
+        for (phase, players, rules) in &games {
+            if !phase.is_changed() || phase.is_added() || !matches!(*phase, GamePhase::Choose) {
+                continue;
+            }
+            let rules = rules.copied().unwrap_or_default();
+
+            for player in players.iter() {
+                let Ok((mut money, territory)) = shoppers.get_mut(*player) else {
+                    continue;
+                };
+
+                let interest = rules.interest(*money);
+                let income = rules.income(territory.map_or(0, |territory| territory.len()));
+                **money += interest + income;
+
+                for (delta, reason) in [
+                    (interest, MoneyChangeReason::Interest),
+                    (income, MoneyChangeReason::Income),
+                ] {
+                    if delta > 0 {
+                        money_changes.send(MoneyChanged {
+                            player: *player,
+                            delta: delta as isize,
+                            reason,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Sold towers return part of everything that was paid for them, including upgrades
+    fn pay_refunds(
+        mut sales: EventReader<TowerSold>,
+        games: Query<Option<&EconomyRules>>,
+        mut shoppers: Query<&mut Money, With<Shopper>>,
+        mut money_changes: EventWriter<MoneyChanged>,
+    ) {
+        for TowerSold {
+            seller,
+            merch,
+            level,
+            game,
+        } in sales.read()
+        {
+            let Ok(mut money) = shoppers.get_mut(*seller) else {
+                continue;
+            };
+            let rules = games.get(*game).ok().flatten().copied().unwrap_or_default();
+
+            let paid = Money::new(*merch.price() * **level as usize);
+            let refund = rules.sell_value(paid);
+            **money += *refund;
+            money_changes.send(MoneyChanged {
+                player: *seller,
+                delta: *refund as isize,
+                reason: MoneyChangeReason::Refund,
+            });
+            info!("Sold {} for {}", merch.name(), refund);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(SystemSet)]
+pub struct EconomySystems;
+
+// Per-game economy configuration, games without it use the default rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Component, Reflect)]
+pub struct EconomyRules {
+    // Flat income paid to every player at the start of each turn
+    pub base_income: usize,
+    // One extra unit of income for every this many tiles of territory, zero disables it
+    pub tiles_per_income: usize,
+    // Percentage of banked money paid as interest each turn
+    pub interest_percent: usize,
+    pub interest_cap: usize,
+    // Percentage of the purchase price returned when selling a tower
+    pub sell_back_percent: usize,
+}
+
+impl Default for EconomyRules {
+    fn default() -> Self {
+        EconomyRules {
+            base_income: 2,
+            tiles_per_income: 10,
+            interest_percent: 10,
+            interest_cap: 5,
+            sell_back_percent: 50,
+        }
+    }
+}
+
+impl EconomyRules {
+    pub fn income(&self, territory_size: usize) -> usize {
+        let territory_income = territory_size
+            .checked_div(self.tiles_per_income)
+            .unwrap_or_default();
+        self.base_income + territory_income
+    }
+
+    pub fn interest(&self, banked: Money) -> usize {
+        (*banked * self.interest_percent / 100).min(self.interest_cap)
+    }
+
+    pub fn sell_value(&self, price: Money) -> Money {
+        Money::new(*price * self.sell_back_percent / 100)
+    }
+}
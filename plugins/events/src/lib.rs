@@ -0,0 +1,317 @@
+use bevy::prelude::*;
+use hexx::Hex;
+use rand::{seq::IteratorRandom, Rng};
+
+use entropy::EntropyBundle;
+use game_loop::{GameLoopSystems, GamePhase, InGame, Turn};
+use health::Health;
+use merchandise::Money;
+use mountain::MountainTile;
+use popups::PopupEvent;
+use resource_deposit::ResourceDepositTile;
+use tilemap::TilemapEntities;
+use tiles::{
+    lasers::{Amplification, Consumption, LaserSystems, Position},
+    DamageTile, TileSystems,
+};
+
+// Rolls environmental events at the start of every choose phase so that rounds do not all play
+// out the same way
+pub struct EventsPlugin {
+    settings: EnvironmentSettings,
+}
+
+impl EventsPlugin {
+    pub fn new(settings: EnvironmentSettings) -> Self {
+        EventsPlugin { settings }
+    }
+}
+
+impl Default for EventsPlugin {
+    fn default() -> Self {
+        EventsPlugin::new(EnvironmentSettings::default())
+    }
+}
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .add_event::<EnvironmentEvent>()
+            .add_systems(
+                Update,
+                (
+                    (Self::roll_events, Self::apply_events, Self::log_events)
+                        .chain()
+                        .after(GameLoopSystems),
+                    Self::amplify_storms
+                        .after(TileSystems::Activate)
+                        .before(LaserSystems),
+                )
+                    .in_set(EventsSystems),
+            );
+    }
+}
+
+impl EventsPlugin {
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn roll_events(
+        mut commands: Commands,
+        mut games: Query<(Entity, &GamePhase, &Turn, &mut EntropyBundle), Changed<GamePhase>>,
+        deposits: Query<(Entity, &Position, &InGame), With<ResourceDepositTile>>,
+        mountains: Query<(Entity, &Position, &InGame), With<MountainTile>>,
+        damageable: Query<(Entity, &Position, &InGame), With<Health>>,
+        settings: Res<EnvironmentSettings>,
+        mut environment_events: EventWriter<EnvironmentEvent>,
+    ) {
+        for (game, phase, turn, mut entropy) in &mut games {
+            if !matches!(phase, GamePhase::Choose) {
+                continue;
+            }
+            // Storms only last for the act phase following the roll
+            commands.entity(game).remove::<SolarStorm>();
+
+            // The first round starts without events
+            if **turn == 0 || !entropy.entropy.gen_bool(settings.chance) {
+                continue;
+            }
+            let Some(kind) = EnvironmentEventKind::ALL
+                .into_iter()
+                .filter(|kind| settings.is_enabled(*kind))
+                .choose(&mut entropy.entropy)
+            else {
+                continue;
+            };
+
+            let amount = kind.target_count();
+            let targets = match kind {
+                EnvironmentEventKind::DepositRefill => {
+                    choose_targets(deposits.iter(), game, amount, &mut entropy.entropy)
+                }
+                EnvironmentEventKind::MountainCollapse => {
+                    choose_targets(mountains.iter(), game, amount, &mut entropy.entropy)
+                }
+                EnvironmentEventKind::Meteor => {
+                    choose_targets(damageable.iter(), game, amount, &mut entropy.entropy)
+                }
+                EnvironmentEventKind::SolarStorm => Vec::new(),
+            };
+
+            // Nothing left on the map for the event to happen to
+            if amount > 0 && targets.is_empty() {
+                continue;
+            }
+
+            info!("Environment event {:?} on turn {}", kind, **turn);
+            environment_events.send(EnvironmentEvent {
+                game,
+                turn: **turn,
+                kind,
+                targets,
+            });
+        }
+    }
+
+    fn apply_events(
+        mut commands: Commands,
+        mut environment_events: EventReader<EnvironmentEvent>,
+        mut deposits: Query<&mut Money, With<ResourceDepositTile>>,
+        tilemaps: Query<&TilemapEntities>,
+    ) {
+        for event in environment_events.read() {
+            for (target, _) in &event.targets {
+                match event.kind {
+                    EnvironmentEventKind::DepositRefill => {
+                        if let Ok(mut money) = deposits.get_mut(*target) {
+                            **money += EnvironmentEventKind::REFILL;
+                        }
+                    }
+                    EnvironmentEventKind::MountainCollapse => {
+                        commands.entity(*target).insert(Health::new(0));
+                    }
+                    EnvironmentEventKind::Meteor => commands.add(DamageTile {
+                        tile: *target,
+                        strength: EnvironmentEventKind::METEOR_DAMAGE,
+                    }),
+                    EnvironmentEventKind::SolarStorm => {}
+                }
+            }
+            if let EnvironmentEventKind::SolarStorm = event.kind {
+                commands.entity(event.game).insert(SolarStorm);
+            }
+
+            let Ok(tilemap) = tilemaps.get_single() else {
+                info!("Found none or multiple tilemaps");
+                continue;
+            };
+            // Events without targets affect the whole map, so they are announced in its center
+            let announced = if event.targets.is_empty() {
+                vec![Hex::ORIGIN]
+            } else {
+                event
+                    .targets
+                    .iter()
+                    .map(|(_, position)| **position)
+                    .collect()
+            };
+            for hex in announced {
+                if let Some(tile_entity) = tilemap.tiles.get(&hex) {
+                    commands.trigger_targets(
+                        PopupEvent {
+                            text: event.kind.label().to_string(),
+                        },
+                        *tile_entity,
+                    );
+                }
+            }
+        }
+    }
+
+    fn log_events(
+        mut commands: Commands,
+        mut environment_events: EventReader<EnvironmentEvent>,
+        mut logs: Query<&mut EnvironmentLog>,
+    ) {
+        for event in environment_events.read() {
+            if let Ok(mut log) = logs.get_mut(event.game) {
+                log.push(event.clone());
+            } else {
+                commands
+                    .entity(event.game)
+                    .insert(EnvironmentLog(vec![event.clone()]));
+            }
+        }
+    }
+
+    fn amplify_storms(
+        mut amplifications: Query<(&mut Amplification, &Consumption), Added<Amplification>>,
+        tiles: Query<&InGame>,
+        storms: Query<(), With<SolarStorm>>,
+    ) {
+        for (mut amplification, consumption) in &mut amplifications {
+            if tiles
+                .get(consumption.tile())
+                .is_ok_and(|game| storms.contains(**game))
+            {
+                **amplification *= SolarStorm::MULTIPLIER;
+            }
+        }
+    }
+}
+
+fn choose_targets<'a>(
+    candidates: impl Iterator<Item = (Entity, &'a Position, &'a InGame)>,
+    game: Entity,
+    amount: usize,
+    rng: &mut impl Rng,
+) -> Vec<(Entity, Position)> {
+    candidates
+        .filter(|(_, _, in_game)| ***in_game == game)
+        .map(|(entity, position, _)| (entity, *position))
+        .choose_multiple(rng, amount)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(SystemSet)]
+pub struct EventsSystems;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Reflect)]
+pub enum EnvironmentEventKind {
+    DepositRefill,
+    MountainCollapse,
+    SolarStorm,
+    Meteor,
+}
+
+impl EnvironmentEventKind {
+    pub const ALL: [Self; 4] = [
+        Self::DepositRefill,
+        Self::MountainCollapse,
+        Self::SolarStorm,
+        Self::Meteor,
+    ];
+    pub const REFILL: usize = 5;
+    pub const METEOR_DAMAGE: usize = 2;
+    pub const METEOR_TARGETS: usize = 2;
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::DepositRefill => "Deposit refilled",
+            Self::MountainCollapse => "Mountain collapsed",
+            Self::SolarStorm => "Solar storm",
+            Self::Meteor => "Meteor strike",
+        }
+    }
+
+    // Name of the kind of event in the settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::DepositRefill => "Deposit refills",
+            Self::MountainCollapse => "Mountain collapses",
+            Self::SolarStorm => "Solar storms",
+            Self::Meteor => "Meteors",
+        }
+    }
+
+    fn target_count(&self) -> usize {
+        match self {
+            Self::Meteor => Self::METEOR_TARGETS,
+            Self::SolarStorm => 0,
+            _ => 1,
+        }
+    }
+}
+
+// Chosen in the shop, `chance` is the probability of any event happening in a round
+#[derive(Clone, Debug)]
+#[derive(Resource)]
+pub struct EnvironmentSettings {
+    pub chance: f64,
+    pub enabled: Vec<EnvironmentEventKind>,
+}
+
+impl EnvironmentSettings {
+    pub fn is_enabled(&self, kind: EnvironmentEventKind) -> bool {
+        self.enabled.contains(&kind)
+    }
+
+    pub fn set_enabled(&mut self, kind: EnvironmentEventKind, enabled: bool) {
+        self.enabled.retain(|enabled_kind| *enabled_kind != kind);
+        if enabled {
+            self.enabled.push(kind);
+        }
+    }
+}
+
+impl Default for EnvironmentSettings {
+    fn default() -> Self {
+        EnvironmentSettings {
+            chance: 0.3,
+            enabled: EnvironmentEventKind::ALL.to_vec(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct EnvironmentEvent {
+    pub game: Entity,
+    pub turn: usize,
+    pub kind: EnvironmentEventKind,
+    // Tiles affected by the event along with where they stood at the time
+    pub targets: Vec<(Entity, Position)>,
+}
+
+// Every environment event of a game in the order they happened, kept for replays
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Deref, DerefMut)]
+pub struct EnvironmentLog(Vec<EnvironmentEvent>);
+
+// Doubles the amplification of every amplifier in the game for this turn
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+pub struct SolarStorm;
+
+impl SolarStorm {
+    pub const MULTIPLIER: usize = 2;
+}
@@ -41,6 +41,7 @@ impl GameLoopPlugin {
                             Player,
                             InGame(new_game),
                             PlayerColorAdjuster((index as f32 / 2.) * 0.5),
+                            PlayerColor::from_index(index),
                         ))
                         .id()
                 })
@@ -98,13 +99,14 @@ impl GameLoopPlugin {
     }
 
     fn complete_drawing_phase(
-        mut games: Query<&mut GamePhase>,
+        mut games: Query<(&mut GamePhase, &mut Turn)>,
         mut events: EventReader<DrawingCompleteEvent>,
     ) {
         for DrawingCompleteEvent { game } in events.read() {
-            if let Ok(mut phase) = games.get_mut(*game) {
+            if let Ok((mut phase, mut turn)) = games.get_mut(*game) {
                 *phase = GamePhase::Choose;
-                info!("Game phase changed to choose")
+                **turn += 1;
+                info!("Game phase changed to choose for turn {}", **turn)
             }
         }
     }
@@ -134,6 +136,11 @@ pub enum GamePhase {
     Draw,
 }
 
+// Counts completed rounds of the game loop, incremented whenever drawing finishes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Component, Deref, DerefMut, Reflect)]
+pub struct Turn(usize);
+
 #[derive(Clone, Copy, Debug)]
 #[derive(Component)]
 pub struct MapSize {
@@ -209,6 +216,7 @@ impl InGame {
 pub struct GameInstanceBundle {
     instance: GameInstance,
     phase: GamePhase,
+    turn: Turn,
     size: MapSize,
 }
 
@@ -221,6 +229,24 @@ pub struct Player;
 #[derive(Component, Deref, Reflect)]
 pub struct PlayerColorAdjuster(pub f32);
 
+// Color identifying a player's territory and other markers on the map
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Deref, Reflect)]
+pub struct PlayerColor(pub Color);
+
+impl PlayerColor {
+    pub const PALETTE: [Color; 4] = [
+        Color::srgb(0.25, 0.41, 0.88),
+        Color::srgb(0.86, 0.08, 0.24),
+        Color::srgb(0.13, 0.55, 0.13),
+        Color::srgb(1., 0.84, 0.),
+    ];
+
+    pub fn from_index(index: usize) -> PlayerColor {
+        Self(Self::PALETTE[index % Self::PALETTE.len()])
+    }
+}
+
 #[derive(Debug)]
 #[derive(Component, Reflect)]
 pub struct Ready;
@@ -15,8 +15,8 @@ impl HealthPlugin {
         query: Query<(Entity, &Health), Changed<Health>>,
     ) {
         for (entity, health) in &query {
-            if **health <= 0 {
-                commands.entity(entity).despawn();
+            if **health == 0 {
+                commands.entity(entity).despawn_recursive();
             }
         }
     }
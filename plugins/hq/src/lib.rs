@@ -1,11 +1,9 @@
 use bevy::{color::palettes, ecs::world::Command, prelude::*};
 
 use game_loop::InGame;
-use health::Health;
-use popups::PopupEvent;
 use tiles::{
-    lasers::{Consumption, Direction, Position},
-    Owner, Tile, TileParameters, TilePlugin,
+    lasers::{Consumption, Direction, LaserKind, Position},
+    Influence, Owner, Tile, TileParameters, TilePlugin,
 };
 pub struct HQPlugin;
 
@@ -20,6 +18,11 @@ impl Plugin for HQPlugin {
 pub struct HQTile;
 
 impl Tile for HQTile {
+    const MAX_HEALTH: Option<usize> = Some(10);
+    const TEXTURE: Option<&'static str> = Some("tiles/hq.png");
+    // Headquarters hold the ground around them against any single building
+    const INFLUENCE: Influence = Influence::new(5, 3);
+
     fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
         HQSpawn {
             position: position,
@@ -40,14 +43,17 @@ impl Tile for HQTile {
         HQActivate {
             tile: entity,
             position: parameters.position,
+            vulnerable: Self::vulnerable(&parameters),
         }
     }
 
-    fn on_hit(&self, entity: Entity, strength: usize, _shooter: Entity) -> Option<impl Command> {
-        Some(HQOnHit {
-            tile: entity,
-            strength,
-        })
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
+
+    // Mining lasers cannot be turned on headquarters
+    fn immune(kind: LaserKind) -> bool {
+        matches!(kind, LaserKind::Mining)
     }
 }
 
@@ -68,39 +74,15 @@ impl Command for HQSpawn {
 pub struct HQActivate {
     tile: Entity,
     position: Position,
+    vulnerable: Vec<Direction>,
 }
 
 impl Command for HQActivate {
     fn apply(self, world: &mut World) {
         world.spawn(Consumption::bundle(
             self.tile,
-            Direction::ALL.to_vec(),
+            self.vulnerable,
             self.position,
         ));
     }
 }
-
-pub struct HQOnHit {
-    tile: Entity,
-    strength: usize,
-}
-
-impl Command for HQOnHit {
-    fn apply(self, world: &mut World) {
-        let mut query = world.query::<(&Position, &mut Health)>();
-        let (position, mut consumer_health) = query
-            .get_mut(world, self.tile)
-            .expect("HQOnHit command should be fired for entity with a health component");
-        **consumer_health -= self.strength;
-        let position = position.clone();
-
-        if let Some(tile_entity) = position.get_tile_entity(world) {
-            world.trigger_targets(
-                PopupEvent {
-                    text: String::from(format!("-{}", self.strength)),
-                },
-                tile_entity,
-            );
-        }
-    }
-}
@@ -4,18 +4,19 @@ use bevy::{
     color::palettes,
     ecs::{system::SystemState, world::Command},
     prelude::*,
-    sprite::MaterialMesh2dBundle,
+    sprite::Anchor,
 };
 
 use game_loop::InGame;
-use health::Health;
 use merchandise::{MerchAppExt, Merchandise, Money};
-use popups::PopupEvent;
 use shop::JustPurchased;
 use tilemap::TilemapLayout;
 use tiles::{
-    lasers::{Consumption, Direction, Laser, Position, Shooter},
-    Owner, Tile, TileParameters, TilePlugin,
+    lasers::{
+        Armor, ArmorKind, Consumption, Direction, Laser, LaserKind, LaserStrength, Position,
+        Shooter,
+    },
+    marker_sprite, tile_material, Influence, Owner, Tile, TileParameters, TilePlugin,
 };
 
 pub struct LaserTowerPlugin;
@@ -61,6 +62,10 @@ impl LaserTowerPlugin {
 pub struct LaserTower;
 
 impl Tile for LaserTower {
+    const MAX_HEALTH: Option<usize> = Some(3);
+    const INFLUENCE: Influence = Influence::new(3, 2);
+    const TEXTURE: Option<&'static str> = Some("tiles/laser_tower.png");
+
     fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
         LaserTowerSpawn { position, player }
     }
@@ -83,16 +88,33 @@ impl Tile for LaserTower {
                 .unwrap_or_else(|| panic!("Laser tower needs a direction")),
             shooter: shooter
                 .unwrap_or_else(|| panic!("Laser tower needs to have a owner to shoot")),
+            strength: Laser::POWER * *parameters.level as usize,
+            vulnerable: Self::vulnerable(&parameters),
+            armor: Self::armor(&parameters),
         }
     }
+
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
+
+    // The emitter is shielded, so lasers coming head on do less damage than from behind
+    fn armor(parameters: &TileParameters) -> Armor {
+        parameters
+            .direction
+            .map(|direction| Armor::default().front(direction, ArmorKind::Reduce(1)))
+            .unwrap_or_default()
+    }
 }
 
 impl Merchandise for LaserTower {
+    const KEY: &'static str = "laser_tower";
     const PRICE: Money = Money::new(10);
     const NAME: &'static str = "Laser Tower";
+    const UPGRADABLE: bool = true;
 
     fn material(asset_server: &AssetServer) -> ColorMaterial {
-        let mut base = <Self as Tile>::material(asset_server);
+        let mut base = tile_material::<Self>(asset_server, 0.);
         base.color.set_alpha(0.6);
         base
     }
@@ -105,13 +127,16 @@ pub struct LaserTowerSpawn {
 
 impl Command for LaserTowerSpawn {
     fn apply(self, world: &mut World) {
-        let mut system_state: SystemState<(
-            Query<&TilemapLayout>,
-            ResMut<Assets<Mesh>>,
-            ResMut<Assets<ColorMaterial>>,
-        )> = SystemState::new(world);
+        // Towers fire the kind of laser their owner had chosen when buying them
+        let kind = world
+            .get::<LaserKind>(self.player)
+            .copied()
+            .unwrap_or_default();
+
+        let mut system_state: SystemState<(Query<&TilemapLayout>, Res<AssetServer>)> =
+            SystemState::new(world);
 
-        let (layout, mut meshes, mut materials) = system_state.get_mut(world);
+        let (layout, asset_server) = system_state.get(world);
 
         let Ok(translation) = layout
             .get_single()
@@ -121,17 +146,21 @@ impl Command for LaserTowerSpawn {
             return;
         };
 
-        let triangle = meshes.add(Triangle2d::new(
-            Vec2::new(-5., 0.),
-            Vec2::new(5., 0.),
-            Vec2::new(0., 40.),
-        ));
-        let white = materials.add(Color::WHITE);
+        // The barrel points north from the center of the tile and is tinted with the laser kind
+        let marker = marker_sprite(
+            &asset_server,
+            "markers/laser_tower.png",
+            kind.color(),
+            kind.color(),
+            Vec2::new(10., 40.),
+            Anchor::BottomCenter,
+        );
 
         if let Some(game) = world.get::<InGame>(self.player) {
             world
                 .spawn((
                     LaserTower,
+                    kind,
                     self.position,
                     Direction::default(),
                     Owner::new(self.player),
@@ -143,15 +172,7 @@ impl Command for LaserTowerSpawn {
                     JustPurchased,
                 ))
                 .with_children(|builder| {
-                    builder.spawn((
-                        LaserTowerMarker,
-                        MaterialMesh2dBundle {
-                            mesh: triangle.into(),
-                            material: white,
-                            transform: Transform::default(),
-                            ..default()
-                        },
-                    ));
+                    builder.spawn((LaserTowerMarker, marker));
                 });
         }
     }
@@ -163,45 +184,32 @@ pub struct LaserTowerActivate {
     position: Position,
     direction: Direction,
     shooter: Entity,
+    strength: usize,
+    vulnerable: Vec<Direction>,
+    armor: Armor,
 }
 
 impl Command for LaserTowerActivate {
     fn apply(self, world: &mut World) {
-        world.spawn(Consumption::bundle(
-            self.tile,
-            Direction::ALL.to_vec(),
-            self.position,
+        world.spawn((
+            Consumption::bundle(self.tile, self.vulnerable, self.position),
+            self.armor,
         ));
+        let kind = world
+            .get::<LaserKind>(self.tile)
+            .copied()
+            .unwrap_or_default();
         world.spawn((
             Laser,
+            kind,
             self.position,
             self.direction,
             Shooter::new(self.shooter),
+            LaserStrength::new(self.strength),
         ));
     }
 }
 
-#[derive(Clone, Debug)]
-pub struct LaserTowerOnHit {
-    tile: Entity,
-    strength: usize,
-}
-
-impl Command for LaserTowerOnHit {
-    fn apply(self, world: &mut World) {
-        let mut laser_tower_health = world
-            .get_mut::<Health>(self.tile)
-            .expect("Laser tower to have Health");
-        **laser_tower_health -= self.strength;
-        world.trigger_targets(
-            PopupEvent {
-                text: String::from(format!("-{}", self.strength)),
-            },
-            self.tile,
-        );
-    }
-}
-
 #[derive(Clone, Copy, Debug)]
 #[derive(Component)]
 pub struct LaserTowerMarker;
@@ -0,0 +1,238 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    color::Alpha,
+    prelude::{
+        default, App, Assets, Changed, Circle, Color, ColorMaterial, ColorMesh2dBundle, Commands,
+        Component, DespawnRecursiveExt, Entity, EventReader, FromWorld, Handle, IntoSystemConfigs,
+        Mesh, Plugin, Query, Rectangle, Res, ResMut, Resource, Timer, TimerMode, Transform, Update,
+        Vec2, Vec3, With, World,
+    },
+    time::Time,
+};
+use rand::Rng;
+
+use game_loop::InGame;
+use health::{Health, HealthSystems};
+use lasers::Position;
+use tilemap::TilemapLayout;
+
+use crate::{LaserDrawSimulation, LaserImpactEvent, LaserVisualSystems};
+
+// Sparks where beams strike tiles and flashes over destroyed tiles
+pub(crate) struct LaserEffectsPlugin;
+
+impl Plugin for LaserEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EffectMeshes>().add_systems(
+            Update,
+            (
+                Self::queue_flashes.before(HealthSystems),
+                (
+                    Self::spawn_sparks,
+                    Self::start_flashes,
+                    Self::update_sparks,
+                    Self::update_flashes,
+                )
+                    .chain()
+                    .after(LaserVisualSystems),
+            ),
+        );
+    }
+}
+
+impl LaserEffectsPlugin {
+    fn spawn_sparks(
+        mut commands: Commands,
+        mut impacts: EventReader<LaserImpactEvent>,
+        meshes: Res<EffectMeshes>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        let mut rng = rand::thread_rng();
+        for impact in impacts.read() {
+            let material = materials.add(impact.color);
+            let count = Spark::COUNT + impact.strength.min(Spark::COUNT);
+            for index in 0..count {
+                // Evenly spread around the impact with some jitter so they do not look like a star
+                let angle = (index as f32 + rng.gen_range(0. ..1.)) * TAU / count as f32;
+                let speed = rng.gen_range(Spark::SPEED / 2. ..Spark::SPEED);
+                commands.spawn((
+                    Spark {
+                        velocity: Vec2::from_angle(angle) * speed,
+                        timer: Timer::from_seconds(Spark::LIFETIME, TimerMode::Once),
+                    },
+                    ColorMesh2dBundle {
+                        mesh: meshes.spark.clone().into(),
+                        material: material.clone(),
+                        transform: Transform::from_translation(impact.position.extend(Spark::Z)),
+                        ..default()
+                    },
+                ));
+            }
+        }
+    }
+
+    // Destroyed tiles are gone before their beams are drawn, so the flash waits for the beam
+    #[allow(clippy::type_complexity)]
+    fn queue_flashes(
+        mut commands: Commands,
+        destroyed: Query<(&Position, &Health), (Changed<Health>, With<InGame>)>,
+        tilemaps: Query<&TilemapLayout>,
+    ) {
+        let Ok(layout) = tilemaps.get_single() else {
+            return;
+        };
+        for (position, _) in destroyed.iter().filter(|(_, health)| ***health == 0) {
+            commands.spawn(PendingFlash {
+                position: layout.hex_to_world_pos(**position),
+                radius: layout.hex_size.x,
+                timeout: Timer::from_seconds(PendingFlash::TIMEOUT, TimerMode::Once),
+            });
+        }
+    }
+
+    // Flashes start when a beam reaches the tile, or once no beams are left to be drawn
+    #[allow(clippy::too_many_arguments)]
+    fn start_flashes(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut impacts: EventReader<LaserImpactEvent>,
+        mut pending: Query<(Entity, &mut PendingFlash)>,
+        simulations: Query<(), With<LaserDrawSimulation>>,
+        meshes: Res<EffectMeshes>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        let impacts = impacts
+            .read()
+            .map(|impact| impact.position)
+            .collect::<Vec<_>>();
+        for (entity, mut flash) in &mut pending {
+            flash.timeout.tick(time.delta());
+            let reached = impacts
+                .iter()
+                .any(|position| position.distance(flash.position) < 1.);
+            let released = reached || (simulations.is_empty() && flash.timeout.finished());
+            if !released {
+                continue;
+            }
+
+            commands.entity(entity).despawn();
+            commands.spawn((
+                TileFlash {
+                    radius: flash.radius,
+                    timer: Timer::from_seconds(TileFlash::DURATION, TimerMode::Once),
+                },
+                ColorMesh2dBundle {
+                    mesh: meshes.flash.clone().into(),
+                    material: materials.add(TileFlash::COLOR),
+                    transform: Transform::from_translation(flash.position.extend(TileFlash::Z))
+                        .with_scale(Vec3::splat(flash.radius)),
+                    ..default()
+                },
+            ));
+        }
+    }
+
+    fn update_sparks(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut sparks: Query<(Entity, &mut Spark, &mut Transform)>,
+    ) {
+        for (entity, mut spark, mut transform) in &mut sparks {
+            spark.timer.tick(time.delta());
+            if spark.timer.finished() {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+            transform.translation += (spark.velocity * time.delta_seconds()).extend(0.);
+            transform.scale = Vec3::splat(spark.timer.fraction_remaining());
+        }
+    }
+
+    // Flashes fade out while growing slightly past the edges of the tile
+    fn update_flashes(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut flashes: Query<(
+            Entity,
+            &mut TileFlash,
+            &mut Transform,
+            &Handle<ColorMaterial>,
+        )>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        for (entity, mut flash, mut transform, material) in &mut flashes {
+            flash.timer.tick(time.delta());
+            if flash.timer.finished() {
+                commands.entity(entity).despawn_recursive();
+                continue;
+            }
+            let growth = 1. + flash.timer.fraction() * (TileFlash::GROWTH - 1.);
+            transform.scale = Vec3::splat(flash.radius * growth);
+            if let Some(material) = materials.get_mut(material) {
+                material.color = TileFlash::COLOR.with_alpha(flash.timer.fraction_remaining());
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Resource)]
+struct EffectMeshes {
+    spark: Handle<Mesh>,
+    // Unit circle scaled to the size of the flashing tile
+    flash: Handle<Mesh>,
+}
+
+impl FromWorld for EffectMeshes {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        EffectMeshes {
+            spark: meshes.add(Rectangle::new(Spark::SIZE, Spark::SIZE)),
+            flash: meshes.add(Circle::new(1.)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct Spark {
+    velocity: Vec2,
+    timer: Timer,
+}
+
+impl Spark {
+    pub const COUNT: usize = 6;
+    pub const SPEED: f32 = 120.;
+    pub const LIFETIME: f32 = 0.4;
+    pub const SIZE: f32 = 4.;
+    pub const Z: f32 = 21.;
+}
+
+// A destroyed tile waiting for the beam that destroyed it to be drawn
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct PendingFlash {
+    position: Vec2,
+    radius: f32,
+    timeout: Timer,
+}
+
+impl PendingFlash {
+    // Seconds to wait for the drawing of the beams to start
+    pub const TIMEOUT: f32 = 0.2;
+}
+
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct TileFlash {
+    radius: f32,
+    timer: Timer,
+}
+
+impl TileFlash {
+    pub const COLOR: Color = Color::WHITE;
+    pub const DURATION: f32 = 0.5;
+    pub const GROWTH: f32 = 1.3;
+    pub const Z: f32 = 12.;
+}
@@ -1,32 +1,58 @@
-use std::collections::HashSet;
-
 use bevy::{
+    color::Alpha,
     prelude::{
-        info, Commands, Component, Deref, Entity, EventReader, EventWriter, FixedUpdate, Gizmos,
-        IntoSystemConfigs, Plugin, Query, Ray2d, Res, SystemSet, Update, Vec2,
+        default, info, App, Assets, BuildChildren, Color, ColorMaterial, ColorMesh2dBundle,
+        Commands, Component, Deref, DespawnRecursiveExt, Entity, Event, EventReader, EventWriter,
+        FromWorld, Handle, IntoSystemConfigs, Mesh, Parent, Plugin, Quat, Query, Rectangle,
+        Reflect, Res, ResMut, Resource, SpatialBundle, SystemSet, Transform, Update, Vec2, Vec3,
+        World,
     },
-    time::{Stopwatch, Time},
+    time::Time,
 };
 
 use game_loop::{DrawingCompleteEvent, GamePhase};
-use lasers::LaserPathEvent;
+use lasers::{LaserPathEvent, LaserSystems};
 use tilemap::TilemapLayout;
 
-pub struct LaserVisualPlugin;
+mod effects;
+pub use effects::*;
+
+pub struct LaserVisualPlugin {
+    playback: LaserPlayback,
+}
+
+impl LaserVisualPlugin {
+    pub fn new(playback: LaserPlayback) -> Self {
+        LaserVisualPlugin { playback }
+    }
+}
+
+impl Default for LaserVisualPlugin {
+    fn default() -> Self {
+        LaserVisualPlugin::new(LaserPlayback::default())
+    }
+}
 
 impl Plugin for LaserVisualPlugin {
-    fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_systems(
-            Update,
-            (
-                Self::spawn_laser_drawing,
-                Self::clean_laser_drawing,
-                Self::tick_simulation,
+    fn build(&self, app: &mut App) {
+        app.register_type::<LaserPlayback>()
+            .insert_resource(self.playback)
+            .init_resource::<BeamMesh>()
+            .add_event::<LaserImpactEvent>()
+            .add_systems(
+                Update,
+                (
+                    Self::spawn_laser_drawing,
+                    Self::tick_simulation,
+                    Self::animate_beams,
+                    Self::send_impacts,
+                    Self::clean_laser_drawing,
+                )
+                    .chain()
+                    .after(LaserSystems)
+                    .in_set(LaserVisualSystems),
             )
-                .chain()
-                .in_set(LaserVisualSystems),
-        )
-        .add_systems(FixedUpdate, Self::draw_lasers.in_set(LaserVisualSystems));
+            .add_plugins(LaserEffectsPlugin);
     }
 }
 
@@ -35,76 +61,143 @@ impl LaserVisualPlugin {
         mut commands: Commands,
         mut laser_path_events: EventReader<LaserPathEvent>,
         tilemaps: Query<&TilemapLayout>,
+        playback: Res<LaserPlayback>,
+        beam_mesh: Res<BeamMesh>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
     ) {
-        let all_paths = laser_path_events
-            .read()
-            .map(|laser_path| laser_path.path.clone())
-            .collect::<Vec<_>>();
+        let all_paths = laser_path_events.read().collect::<Vec<_>>();
+        // Skipped turns resolve without any beams, so drawing completes right away
+        if playback.skip {
+            return;
+        }
         for tilemap in &tilemaps {
             let paths = all_paths
                 .iter()
                 .map(|laser_path| {
                     LaserPath::new(
                         laser_path
+                            .path
                             .iter()
                             .map(|hex_position| tilemap.hex_to_world_pos(**hex_position))
                             .collect::<Vec<_>>(),
                     )
+                    .with_jumps(laser_path.jumps.clone())
+                    .with_strengths(laser_path.strengths.clone())
+                    .with_color(laser_path.kind.color())
                 })
                 .collect::<Vec<_>>();
-            let total_paths = paths.len();
-            if total_paths > 0 {
-                commands.spawn(LaserDrawSimulation {
-                    paths,
-                    stopwatch: Stopwatch::new(),
-                    remaining_paths: HashSet::from_iter(0..total_paths),
-                });
+            if paths.is_empty() {
+                continue;
             }
-        }
-    }
 
-    fn draw_lasers(mut draw_simulations: Query<&mut LaserDrawSimulation>, mut gizmos: Gizmos) {
-        for mut simulation in &mut draw_simulations {
-            let time = simulation.stopwatch.elapsed_secs();
-            // Store the data for each start, end of all the laser segments
-            let all_laser_segments = simulation
+            let simulation = LaserDrawSimulation::new(paths);
+            let segments = simulation
                 .paths
                 .iter()
-                .map(|laser_path| {
-                    laser_path
-                        .windows(2)
-                        .map(|segment| {
-                            let start = segment[0];
-                            let end = segment[1];
-                            [start, end]
+                .enumerate()
+                .flat_map(|(path_index, path)| {
+                    let core = materials.add(path.color);
+                    let glow = materials.add(path.color.with_alpha(LaserPath::GLOW_ALPHA));
+                    (0..path.len().saturating_sub(1))
+                        .filter(|segment| !path.is_jump(segment + 1))
+                        .flat_map(move |segment| {
+                            [
+                                (BeamSegment::new(path_index, segment, false), core.clone()),
+                                (BeamSegment::new(path_index, segment, true), glow.clone()),
+                            ]
                         })
-                        .collect::<Vec<_>>()
                 })
                 .collect::<Vec<_>>();
 
-            for (laser_path_index, laser_path) in all_laser_segments.iter().enumerate() {
-                let mut laser_time = time;
-                let number_of_segments = laser_path.len();
-                for (index, segment) in laser_path.iter().enumerate() {
-                    let start = segment[0];
-                    let end = segment[1];
-                    let ray = Ray2d::new(start, end - start);
-                    let point_reached =
-                        ray.get_point(laser_time * LaserDrawSimulation::LASER_SPEED);
-                    if point_reached.distance(start) > end.distance(start) {
-                        gizmos
-                            .arrow_2d(start, end, bevy::color::palettes::css::RED)
-                            .with_tip_length(25.);
-                        laser_time -= end.distance(start) / LaserDrawSimulation::LASER_SPEED;
-                        if index == number_of_segments - 1 {
-                            simulation.remaining_paths.remove(&laser_path_index);
-                        }
-                    } else {
-                        gizmos
-                            .arrow_2d(start, point_reached, bevy::color::palettes::css::RED)
-                            .with_tip_length(25.);
-                        break;
+            commands
+                .spawn((simulation, SpatialBundle::default()))
+                .with_children(|builder| {
+                    for (segment, material) in segments {
+                        builder.spawn((
+                            segment,
+                            ColorMesh2dBundle {
+                                mesh: (**beam_mesh).clone().into(),
+                                material,
+                                // Hidden until the beam starts crossing the segment
+                                transform: Transform::from_scale(Vec3::ZERO),
+                                ..default()
+                            },
+                        ));
                     }
+                });
+        }
+    }
+
+    fn tick_simulation(
+        time: Res<Time>,
+        playback: Res<LaserPlayback>,
+        mut simulations: Query<&mut LaserDrawSimulation>,
+    ) {
+        for mut simulation in &mut simulations {
+            simulation.previous = simulation.travelled;
+            if playback.skip {
+                simulation.travelled = simulation.end();
+            } else {
+                simulation.travelled +=
+                    time.delta_seconds() * LaserDrawSimulation::LASER_SPEED * playback.speed;
+            }
+        }
+    }
+
+    // Segments grow from their start as the beam front moves across them
+    fn animate_beams(
+        simulations: Query<&LaserDrawSimulation>,
+        mut segments: Query<(&Parent, &BeamSegment, &mut Transform)>,
+    ) {
+        for (parent, segment, mut transform) in &mut segments {
+            let Ok(simulation) = simulations.get(**parent) else {
+                continue;
+            };
+            let path = &simulation.paths[segment.path];
+            let start = path[segment.segment];
+            let end = path[segment.segment + 1];
+            let drawn = (simulation.travelled - path.distances[segment.segment])
+                .clamp(0., start.distance(end));
+            if drawn <= 0. {
+                continue;
+            }
+
+            let direction = (end - start).normalize_or_zero();
+            let mut width = path.width(segment.segment + 1);
+            let mut z = LaserPath::Z;
+            if segment.glow {
+                width *= LaserPath::GLOW_SCALE;
+                z -= 0.1;
+            }
+            *transform = Transform {
+                translation: (start + direction * drawn / 2.).extend(z),
+                rotation: Quat::from_rotation_z(direction.to_angle()),
+                scale: Vec3::new(drawn, width, 1.),
+            };
+        }
+    }
+
+    // Every point a beam reaches after leaving its tower is a tile it bounced off, passed through
+    // or hit
+    fn send_impacts(
+        simulations: Query<&LaserDrawSimulation>,
+        mut impacts: EventWriter<LaserImpactEvent>,
+    ) {
+        for simulation in &simulations {
+            for path in &simulation.paths {
+                for (index, point) in path.iter().enumerate().skip(1) {
+                    let distance = path.distances[index];
+                    if path.is_jump(index)
+                        || distance <= simulation.previous
+                        || distance > simulation.travelled
+                    {
+                        continue;
+                    }
+                    impacts.send(LaserImpactEvent {
+                        position: *point,
+                        color: path.color,
+                        strength: path.strength(index),
+                    });
                 }
             }
         }
@@ -123,31 +216,149 @@ impl LaserVisualPlugin {
             events.send(DrawingCompleteEvent { game });
         }
         for (entity, simulation) in &draw_simulations {
-            if simulation.remaining_paths.is_empty() {
-                commands.entity(entity).despawn();
+            if simulation.is_finished() {
+                commands.entity(entity).despawn_recursive();
                 info!("Laser draw simulation cleared")
             }
         }
     }
-
-    fn tick_simulation(time: Res<Time>, mut simulations: Query<&mut LaserDrawSimulation>) {
-        for mut simulation in &mut simulations {
-            simulation.stopwatch.tick(time.delta());
-        }
-    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[derive(SystemSet)]
 pub struct LaserVisualSystems;
 
+// How fast lasers are drawn, skipping draws no beams at all and finishes the turn immediately
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+pub struct LaserPlayback {
+    pub speed: f32,
+    pub skip: bool,
+}
+
+impl Default for LaserPlayback {
+    fn default() -> Self {
+        LaserPlayback {
+            speed: 1.,
+            skip: false,
+        }
+    }
+}
+
+// Unit square stretched along every beam segment
+#[derive(Clone, Debug)]
+#[derive(Resource, Deref)]
+struct BeamMesh(Handle<Mesh>);
+
+impl FromWorld for BeamMesh {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        BeamMesh(meshes.add(Rectangle::new(1., 1.)))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct BeamSegment {
+    path: usize,
+    segment: usize,
+    // Wide translucent copy of the segment drawn below it
+    glow: bool,
+}
+
+impl BeamSegment {
+    fn new(path: usize, segment: usize, glow: bool) -> Self {
+        BeamSegment {
+            path,
+            segment,
+            glow,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 #[derive(Component, Deref)]
-pub struct LaserPath(Vec<Vec2>);
+pub struct LaserPath {
+    #[deref]
+    points: Vec<Vec2>,
+    // Indices of points reached by teleporting from the previous point
+    jumps: Vec<usize>,
+    strengths: Vec<usize>,
+    // Distance the beam travels before reaching each point, jumps take no time
+    distances: Vec<f32>,
+    color: Color,
+}
 
 impl LaserPath {
+    pub const Z: f32 = 20.;
+    pub const MIN_WIDTH: f32 = 3.;
+    pub const WIDTH_PER_STRENGTH: f32 = 2.;
+    pub const MAX_WIDTH: f32 = 16.;
+    pub const GLOW_SCALE: f32 = 3.;
+    pub const GLOW_ALPHA: f32 = 0.25;
+
     pub fn new(points: Vec<Vec2>) -> Self {
-        LaserPath(points)
+        LaserPath {
+            points,
+            jumps: Vec::new(),
+            strengths: Vec::new(),
+            distances: Vec::new(),
+            color: Color::Srgba(bevy::color::palettes::css::RED),
+        }
+        .with_distances()
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_jumps(mut self, jumps: Vec<usize>) -> Self {
+        self.jumps = jumps;
+        self.with_distances()
+    }
+
+    pub fn with_strengths(mut self, strengths: Vec<usize>) -> Self {
+        self.strengths = strengths;
+        self
+    }
+
+    pub fn is_jump(&self, index: usize) -> bool {
+        self.jumps.contains(&index)
+    }
+
+    // Strength of the beam on the segment ending at the point, paths without strengths are drawn
+    // at the base laser power
+    pub fn strength(&self, index: usize) -> usize {
+        self.strengths
+            .get(index)
+            .copied()
+            .unwrap_or(lasers::Laser::POWER)
+    }
+
+    pub fn width(&self, index: usize) -> f32 {
+        (Self::MIN_WIDTH + Self::WIDTH_PER_STRENGTH * self.strength(index) as f32)
+            .min(Self::MAX_WIDTH)
+    }
+
+    pub fn length(&self) -> f32 {
+        self.distances.last().copied().unwrap_or_default()
+    }
+
+    fn with_distances(mut self) -> Self {
+        let mut travelled = 0.;
+        self.distances = self
+            .points
+            .iter()
+            .enumerate()
+            .map(|(index, point)| {
+                if index > 0 && !self.jumps.contains(&index) {
+                    travelled += self.points[index - 1].distance(*point);
+                }
+                travelled
+            })
+            .collect();
+        self
     }
 }
 
@@ -155,10 +366,38 @@ impl LaserPath {
 #[derive(Component)]
 pub struct LaserDrawSimulation {
     pub paths: Vec<LaserPath>,
-    pub stopwatch: Stopwatch,
-    pub remaining_paths: HashSet<usize>,
+    // Distance every beam front has moved since the drawing started, and as of the last frame
+    pub travelled: f32,
+    pub previous: f32,
 }
 
 impl LaserDrawSimulation {
     const LASER_SPEED: f32 = 300.;
+    // Seconds the finished beams stay on screen at normal speed
+    const LINGER: f32 = 0.3;
+
+    pub fn new(paths: Vec<LaserPath>) -> Self {
+        LaserDrawSimulation {
+            paths,
+            travelled: 0.,
+            previous: 0.,
+        }
+    }
+
+    fn end(&self) -> f32 {
+        self.paths.iter().map(LaserPath::length).fold(0., f32::max)
+            + Self::LASER_SPEED * Self::LINGER
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.travelled >= self.end()
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct LaserImpactEvent {
+    pub position: Vec2,
+    pub color: Color,
+    pub strength: usize,
 }
@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use bevy::prelude::*;
 
 use game_loop::{ActionCompleteEvent, GamePhase, MapSize};
@@ -10,17 +12,34 @@ impl Plugin for LaserPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<LaserPathEvent>()
             .add_event::<LaserHitEvent>()
+            .add_event::<ShieldHitEvent>()
             .add_systems(
                 Update,
-                (Self::track_lasers, Self::despawn_lasers).in_set(LaserSystems),
+                (
+                    Self::track_lasers,
+                    Self::despawn_lasers,
+                    Self::despawn_consumptions,
+                )
+                    .in_set(LaserSystems),
             );
     }
 }
 
 impl LaserPlugin {
-    #[allow(clippy::type_complexity)]
+    // Every tile that interacts with lasers is activated with a `Consumption`, even if none of its
+    // faces are vulnerable
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     fn track_lasers(
-        lasers: Query<(&Position, &Direction, &Shooter), With<Laser>>,
+        lasers: Query<
+            (
+                &Position,
+                &Direction,
+                &Shooter,
+                Option<&LaserStrength>,
+                Option<&LaserKind>,
+            ),
+            With<Laser>,
+        >,
         colliders: Query<
             (
                 Entity,
@@ -30,19 +49,19 @@ impl LaserPlugin {
                 Option<&YReflection>,
                 Option<&Rotation>,
                 Option<&Amplification>,
-                Option<&Consumption>,
+                Option<&Splitting>,
+                Option<&Teleportation>,
+                Option<&Scattering>,
+                Option<&Attenuation>,
+                &Consumption,
+                Option<&Armor>,
             ),
-            Or<(
-                With<Refraction>,
-                With<Reflection>,
-                With<YReflection>,
-                With<Rotation>,
-                With<Amplification>,
-                With<Consumption>,
-            )>,
+            With<Consumption>,
         >,
+        mut shields: Query<&mut ShieldField>,
         mut laser_hit_events: EventWriter<LaserHitEvent>,
         mut laser_path_events: EventWriter<LaserPathEvent>,
+        mut shield_hit_events: EventWriter<ShieldHitEvent>,
         games: Query<(Entity, &MapSize, &GamePhase)>,
         mut events: EventWriter<ActionCompleteEvent>,
     ) {
@@ -54,96 +73,224 @@ impl LaserPlugin {
             return;
         };
 
-        'all_lasers: for (laser_position, laser_direction, laser_shooter) in &lasers {
-            let mut path = Vec::new();
-            let mut current_position = *laser_position;
-            path.push(current_position);
-            let mut current_direction = *laser_direction;
+        // Every beam crossing a scattering tile takes the next of its deflections
+        let mut scatter_counts: HashMap<Entity, usize> = HashMap::new();
+
+        for (laser_position, laser_direction, laser_shooter, laser_strength, laser_kind) in &lasers
+        {
+            // Beams split off a laser keep its kind
+            let kind = laser_kind.copied().unwrap_or_default();
             info!(
                 "Simulating laser from starting position {:?} and direction {:?}",
-                current_position, current_direction
+                laser_position, laser_direction
             );
-            let mut strength = Laser::POWER;
-
-            loop {
-                let next_position: Position =
-                    current_position.neighbor(current_direction.as_hex()).into();
-                // Change this later to exit the while loop appropriately and still progress the laser path outside the tilemap for visual effect
-                // Need to consider edge case of reflector directly back on same path...
-                if path.contains(&next_position) {
-                    info!("Reached previously visited position");
-                    break;
-                }
-
-                if (*current_position).unsigned_distance_to(Hex::ORIGIN)
-                    > 3 * std::cmp::max(map_size.half_width, map_size.half_height) as u32
-                {
-                    info!("Laser out of bounds off map");
-                    break;
-                }
+            // Splitters fork beams off the laser, each beam is traced until it ends and reports
+            // its own path
+            let mut beams = vec![Beam::new(
+                *laser_position,
+                *laser_direction,
+                laser_strength.map_or(Laser::POWER, |strength| **strength),
+            )];
+            let mut beam_count = 1;
+
+            'all_beams: while let Some(Beam {
+                mut path,
+                mut jumps,
+                mut strengths,
+                position: mut current_position,
+                direction: mut current_direction,
+                mut strength,
+            }) = beams.pop()
+            {
+                loop {
+                    let next_position: Position =
+                        current_position.neighbor(current_direction.as_hex()).into();
+                    // Change this later to exit the while loop appropriately and still progress the laser path outside the tilemap for visual effect
+                    // Need to consider edge case of reflector directly back on same path...
+                    if path.contains(&next_position) {
+                        info!("Reached previously visited position");
+                        break;
+                    }
 
-                if let Some((
-                    collider,
-                    _,
-                    refraction,
-                    reflection,
-                    y_reflection,
-                    rotation,
-                    amplification,
-                    consumption,
-                )) = colliders
-                    .iter()
-                    .find(|(_, position, _, _, _, _, _, _)| **position == next_position)
-                {
-                    // Update the path with a new segment point upon collision with a tile
-                    path.push(next_position);
-
-                    if consumption.is_some()
-                        && consumption
-                            .unwrap()
-                            .vulnerable
-                            .iter()
-                            .any(|&direction| direction == current_direction)
+                    if (*current_position).unsigned_distance_to(Hex::ORIGIN)
+                        > 3 * std::cmp::max(map_size.half_width, map_size.half_height) as u32
                     {
-                        info!("Laser hit event found at {:?}", next_position);
-                        laser_hit_events.send(LaserHitEvent {
-                            consumer: collider,
-                            strength,
-                            shooter: **laser_shooter,
-                        });
-                        laser_path_events.send(LaserPathEvent { path: path.clone() });
-                        continue 'all_lasers;
+                        info!("Laser out of bounds off map");
+                        break;
                     }
 
-                    if let Some(refracted_direction) =
-                        refraction.and_then(|refraction| refraction.refract(current_direction))
-                    {
-                        current_direction = refracted_direction;
-                        info!("Laser refracted")
+                    // Shields soak up the strength of other players' beams on every hex they cover,
+                    // except for the generator itself which drains its shield when it is hit
+                    if let Some(mut shield) = shields.iter_mut().find(|shield| {
+                        shield.owner != **laser_shooter
+                            && shield.capacity > 0
+                            && shield.center != next_position
+                            && shield.covers(&next_position)
+                    }) {
+                        let absorbed = shield.drain(ShieldField::ABSORPTION.min(strength));
+                        strength -= absorbed;
+                        info!("Shield absorbed {} at {:?}", absorbed, next_position);
+                        shield_hit_events.send(ShieldHitEvent {
+                            tile: shield.tile,
+                            position: next_position,
+                            absorbed,
+                        });
+                        if strength == 0 {
+                            path.push(next_position);
+                            strengths.push(strength + absorbed);
+                            laser_path_events.send(LaserPathEvent {
+                                path,
+                                jumps,
+                                strengths,
+                                kind,
+                            });
+                            continue 'all_beams;
+                        }
                     }
-                    if let Some(reflected_direction) = reflection
-                        .and_then(|reflection| Some(reflection.reflect(current_direction)))
+
+                    if let Some((
+                        collider,
+                        _,
+                        refraction,
+                        reflection,
+                        y_reflection,
+                        rotation,
+                        amplification,
+                        splitting,
+                        teleportation,
+                        scattering,
+                        attenuation,
+                        consumption,
+                        armor,
+                    )) = colliders
+                        .iter()
+                        .find(|(_, position, ..)| **position == next_position)
                     {
-                        current_direction = reflected_direction;
-                    }
-                    if let Some(y_reflection) = y_reflection {
-                        current_direction = y_reflection.reflect(current_direction);
-                    }
-                    if let Some(rotation) = rotation {
-                        current_direction = rotation.rotate(current_direction);
-                    }
-                    if let Some(amplification) = amplification {
-                        strength += **amplification;
+                        // Update the path with a new segment point upon collision with a tile
+                        path.push(next_position);
+                        strengths.push(strength);
+
+                        // Armor takes precedence over the vulnerable faces of the tile
+                        let face = current_direction.opposite();
+                        let damage = match armor.and_then(|armor| armor.get(face)) {
+                            Some(ArmorKind::Absorb) => {
+                                info!("Laser absorbed by armor at {:?}", next_position);
+                                laser_path_events.send(LaserPathEvent {
+                                    path,
+                                    jumps,
+                                    strengths,
+                                    kind,
+                                });
+                                continue 'all_beams;
+                            }
+                            Some(ArmorKind::Deflect) => {
+                                info!("Laser deflected by armor at {:?}", next_position);
+                                current_direction = current_direction.clockwise(1);
+                                current_position = next_position;
+                                continue;
+                            }
+                            Some(ArmorKind::Reduce(reduction)) => {
+                                Some(strength.saturating_sub(reduction))
+                            }
+                            None => consumption
+                                .vulnerable
+                                .contains(&current_direction)
+                                .then_some(strength),
+                        };
+
+                        if let Some(damage) = damage {
+                            if damage > 0 {
+                                info!("Laser hit event found at {:?}", next_position);
+                                laser_hit_events.send(LaserHitEvent {
+                                    consumer: collider,
+                                    strength: damage,
+                                    shooter: **laser_shooter,
+                                    kind,
+                                });
+                            }
+                            if consumption.absorbs {
+                                laser_path_events.send(LaserPathEvent {
+                                    path,
+                                    jumps,
+                                    strengths,
+                                    kind,
+                                });
+                                continue 'all_beams;
+                            }
+                        }
+
+                        if let Some(refracted_direction) =
+                            refraction.and_then(|refraction| refraction.refract(current_direction))
+                        {
+                            current_direction = refracted_direction;
+                            info!("Laser refracted")
+                        }
+                        if let Some(reflected_direction) = reflection
+                            .and_then(|reflection| Some(reflection.reflect(current_direction)))
+                        {
+                            current_direction = reflected_direction;
+                        }
+                        if let Some(y_reflection) = y_reflection {
+                            current_direction = y_reflection.reflect(current_direction);
+                        }
+                        if let Some(rotation) = rotation {
+                            current_direction = rotation.rotate(current_direction);
+                        }
+                        if let Some(scattering) = scattering {
+                            let crossing = scatter_counts.entry(collider).or_default();
+                            current_direction = scattering.scatter(current_direction, *crossing);
+                            *crossing += 1;
+                        }
+                        if let Some(amplification) = amplification {
+                            strength += **amplification;
+                        }
+                        if let Some(attenuation) = attenuation {
+                            strength = attenuation.weaken(strength);
+                        }
+                        if let Some([kept_direction, forked_direction]) =
+                            splitting.and_then(|splitting| splitting.split(current_direction))
+                        {
+                            // The halves share the beam's strength, beams too weak to share it
+                            // or past the cap carry on unsplit so splitter loops end
+                            let forked_strength = strength / 2;
+                            if beam_count < Laser::MAX_BEAMS && forked_strength > 0 {
+                                info!("Laser split at {:?}", next_position);
+                                strength -= forked_strength;
+                                current_direction = kept_direction;
+                                beams.push(Beam::new(
+                                    next_position,
+                                    forked_direction,
+                                    forked_strength,
+                                ));
+                                beam_count += 1;
+                            }
+                        }
+                        // The beam jumps straight to the exit without crossing the hexes between
+                        if let Some(teleportation) = teleportation {
+                            info!("Laser teleported to {:?}", teleportation.exit);
+                            path.push(teleportation.exit);
+                            jumps.push(path.len() - 1);
+                            strengths.push(strength);
+                            current_direction = teleportation.direction;
+                            current_position = teleportation.exit;
+                            continue;
+                        }
                     }
+                    current_position = next_position;
                 }
-                current_position = next_position;
-            }
 
-            // Update path with ending point, particularly important to mark if
-            // no collisions or consumptions of the laser occur
-            path.push(current_position);
-            info!("Sent uninterrupted path event {:?}", path);
-            laser_path_events.send(LaserPathEvent { path });
+                // Update path with ending point, particularly important to mark if
+                // no collisions or consumptions of the laser occur
+                path.push(current_position);
+                strengths.push(strength);
+                info!("Sent uninterrupted path event {:?}", path);
+                laser_path_events.send(LaserPathEvent {
+                    path,
+                    jumps,
+                    strengths,
+                    kind,
+                });
+            }
         }
 
         events.send(ActionCompleteEvent { game });
@@ -164,6 +311,23 @@ impl LaserPlugin {
             }
         }
     }
+
+    // Tiles are activated again at the start of every act phase, so last turn's colliders must not
+    // outlive the tiles that spawned them
+    fn despawn_consumptions(
+        mut commands: Commands,
+        games: Query<&GamePhase, Changed<GamePhase>>,
+        consumptions: Query<Entity, With<Consumption>>,
+    ) {
+        if games
+            .get_single()
+            .is_ok_and(|game_phase| matches!(game_phase, GamePhase::Draw))
+        {
+            for consumption in &consumptions {
+                commands.entity(consumption).despawn();
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -175,11 +339,26 @@ pub struct LaserHitEvent {
     pub strength: usize,
     pub consumer: Entity,
     pub shooter: Entity,
+    pub kind: LaserKind,
 }
 
 #[derive(Event)]
 pub struct LaserPathEvent {
     pub path: Vec<Position>,
+    // Indices into `path` of points the laser teleported to, the segment ending at each of these
+    // is a jump rather than a line across the map
+    pub jumps: Vec<usize>,
+    // Strength of the beam on the segment ending at each point of `path`, the first entry is the
+    // strength it was fired or split off with
+    pub strengths: Vec<usize>,
+    pub kind: LaserKind,
+}
+
+#[derive(Event)]
+pub struct ShieldHitEvent {
+    pub tile: Entity,
+    pub position: Position,
+    pub absorbed: usize,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -308,6 +487,74 @@ pub struct Laser;
 
 impl Laser {
     pub const POWER: usize = 1;
+    // Upper bound on the beams a single laser can be split into
+    pub const MAX_BEAMS: usize = 16;
+}
+
+// A beam of a laser that is still being traced, starting from where it was fired or split off
+struct Beam {
+    path: Vec<Position>,
+    jumps: Vec<usize>,
+    strengths: Vec<usize>,
+    position: Position,
+    direction: Direction,
+    strength: usize,
+}
+
+impl Beam {
+    fn new(position: Position, direction: Direction, strength: usize) -> Self {
+        Beam {
+            path: vec![position],
+            jumps: Vec::new(),
+            strengths: vec![strength],
+            position,
+            direction,
+            strength,
+        }
+    }
+}
+
+// What a laser does to the tiles it hits, lasers without one deal damage
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[derive(Component, Reflect)]
+pub enum LaserKind {
+    #[default]
+    Damage,
+    // Extracts extra money from deposits and leaves headquarters unharmed
+    Mining,
+    // Deals no damage but disables the tile it hits for the next act phase
+    Disruptor,
+}
+
+impl LaserKind {
+    pub const ALL: [LaserKind; 3] = [LaserKind::Damage, LaserKind::Mining, LaserKind::Disruptor];
+
+    pub fn color(&self) -> Color {
+        match self {
+            LaserKind::Damage => Color::Srgba(bevy::color::palettes::css::RED),
+            LaserKind::Mining => Color::Srgba(bevy::color::palettes::css::GOLD),
+            LaserKind::Disruptor => Color::Srgba(bevy::color::palettes::css::DEEP_SKY_BLUE),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            LaserKind::Damage => "Damage",
+            LaserKind::Mining => "Mining",
+            LaserKind::Disruptor => "Disruptor",
+        }
+    }
+}
+
+// Starting strength of a laser, lasers without one fire at `Laser::POWER`
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Deref, Reflect)]
+pub struct LaserStrength(usize);
+
+impl LaserStrength {
+    pub fn new(strength: usize) -> Self {
+        LaserStrength(strength)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default)]
@@ -335,6 +582,28 @@ impl Refraction {
     }
 }
 
+// Forks lasers entering through the back of the tile into the two directions either side of
+// its facing, each carrying half the strength but never less than `Laser::POWER`
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect)]
+pub struct Splitting {
+    facing: Direction,
+}
+
+impl Splitting {
+    pub fn new(facing: Direction) -> Self {
+        Splitting { facing }
+    }
+
+    pub fn split(&self, incoming: Direction) -> Option<[Direction; 2]> {
+        if self.facing.front_directions().contains(&incoming) {
+            Some([self.facing.clockwise(1), self.facing.counterclockwise(1)])
+        } else {
+            None
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 #[derive(Component, Reflect)]
 pub struct Reflection {
@@ -364,7 +633,7 @@ impl Reflection {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[derive(Component, Reflect)]
 pub enum YReflection {
     #[default]
@@ -373,6 +642,13 @@ pub enum YReflection {
 }
 
 impl YReflection {
+    pub fn toggled(&self) -> Self {
+        match self {
+            YReflection::LeftTilt => YReflection::RightTilt,
+            YReflection::RightTilt => YReflection::LeftTilt,
+        }
+    }
+
     pub fn reflect(&self, incoming: Direction) -> Direction {
         match (self, incoming) {
             (YReflection::RightTilt, Direction::North) => Direction::Northeast,
@@ -391,7 +667,7 @@ impl YReflection {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 #[derive(Component, Deref, DerefMut, Reflect)]
 pub struct Rotation(u8);
 
@@ -409,6 +685,143 @@ impl Rotation {
     }
 }
 
+// Deflects lasers by a number of clockwise steps rolled when the tile is activated, each laser
+// crossing the tile in a turn takes the next roll
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Reflect)]
+pub struct Scattering {
+    offsets: Vec<u8>,
+}
+
+impl Scattering {
+    pub fn new(offsets: Vec<u8>) -> Self {
+        Scattering { offsets }
+    }
+
+    pub fn scatter(&self, incoming: Direction, crossing: usize) -> Direction {
+        if self.offsets.is_empty() {
+            return incoming;
+        }
+        incoming.clockwise(self.offsets[crossing % self.offsets.len()])
+    }
+}
+
+// Strength taken from every laser passing through the tile, lasers are never worn down below
+// `Laser::POWER` so the tile weakens beams instead of stopping them
+#[derive(Clone, Copy, Debug, Default)]
+#[derive(Component, Reflect, Deref, DerefMut)]
+pub struct Attenuation(usize);
+
+impl Attenuation {
+    pub fn new(strength: usize) -> Self {
+        Attenuation(strength)
+    }
+
+    pub fn weaken(&self, strength: usize) -> usize {
+        strength
+            .saturating_sub(self.0)
+            .max(Laser::POWER.min(strength))
+    }
+}
+
+// Sends lasers on to `exit`, leaving in the given direction
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+pub struct Teleportation {
+    #[reflect(ignore)]
+    pub exit: Position,
+    pub direction: Direction,
+}
+
+impl Teleportation {
+    pub fn new(exit: Position, direction: Direction) -> Self {
+        Teleportation { exit, direction }
+    }
+}
+
+// Protection on the faces of a tile, keyed by the face a laser enters through rather than the
+// direction it travels in
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Reflect)]
+pub struct Armor {
+    faces: Vec<(Direction, ArmorKind)>,
+}
+
+impl Armor {
+    pub fn face(mut self, face: Direction, kind: ArmorKind) -> Self {
+        self.faces.retain(|(armored, _)| *armored != face);
+        self.faces.push((face, kind));
+        self
+    }
+
+    // Armors the three faces on the side the tile is facing
+    pub fn front(self, facing: Direction, kind: ArmorKind) -> Self {
+        facing
+            .front_directions()
+            .into_iter()
+            .fold(self, |armor, face| armor.face(face, kind))
+    }
+
+    // Armors the three faces on the side opposite to where the tile is facing
+    pub fn back(self, facing: Direction, kind: ArmorKind) -> Self {
+        facing
+            .back_directions()
+            .into_iter()
+            .fold(self, |armor, face| armor.face(face, kind))
+    }
+
+    pub fn get(&self, face: Direction) -> Option<ArmorKind> {
+        self.faces
+            .iter()
+            .find(|(armored, _)| *armored == face)
+            .map(|(_, kind)| *kind)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.faces.is_empty()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Reflect)]
+pub enum ArmorKind {
+    // Stops the laser without damaging the tile
+    Absorb,
+    // The laser glances off without damaging the tile and leaves turned one step clockwise
+    Deflect,
+    // The tile is hit with the laser strength lowered by this amount
+    Reduce(usize),
+}
+
+// Protects every hex within `radius` of `center` from lasers shot by anyone but `owner`, until
+// the capacity runs dry
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+pub struct ShieldField {
+    pub tile: Entity,
+    pub owner: Entity,
+    #[reflect(ignore)]
+    pub center: Position,
+    pub radius: u32,
+    pub capacity: usize,
+}
+
+impl ShieldField {
+    // Strength taken from a beam on each shielded hex it crosses
+    pub const ABSORPTION: usize = 1;
+
+    pub fn covers(&self, position: &Position) -> bool {
+        self.center.unsigned_distance_to(**position) <= self.radius
+    }
+
+    // Takes up to `amount` from the capacity and returns how much was actually absorbed
+    pub fn drain(&mut self, amount: usize) -> usize {
+        let drained = amount.min(self.capacity);
+        self.capacity -= drained;
+        drained
+    }
+}
+
 #[derive(Clone, Copy, Debug, Default)]
 #[derive(Component, Reflect, Deref, DerefMut)]
 pub struct Amplification(usize);
@@ -424,17 +837,141 @@ impl Amplification {
 pub struct Consumption {
     entity: Entity,
     vulnerable: Vec<Direction>,
+    // Lasers stop at an absorbing tile, otherwise they are redirected or amplified as usual after
+    // hitting a vulnerable face
+    absorbs: bool,
 }
 
 impl Consumption {
-    fn new(tile: Entity, vulnerable: Vec<Direction>) -> Self {
+    fn new(tile: Entity, vulnerable: Vec<Direction>, absorbs: bool) -> Self {
         Consumption {
             entity: tile,
             vulnerable,
+            absorbs,
         }
     }
 
     pub fn bundle(tile: Entity, vulnerable: Vec<Direction>, position: Position) -> impl Bundle {
-        (Consumption::new(tile, vulnerable), position)
+        (Consumption::new(tile, vulnerable, true), position)
+    }
+
+    pub fn passing_bundle(
+        tile: Entity,
+        vulnerable: Vec<Direction>,
+        position: Position,
+    ) -> impl Bundle {
+        (Consumption::new(tile, vulnerable, false), position)
+    }
+
+    pub fn tile(&self) -> Entity {
+        self.entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::*;
+    use hexx::Hex;
+
+    use game_loop::{ActionCompleteEvent, GamePhase, MapSize};
+
+    use super::{
+        Consumption, Direction, Laser, LaserHitEvent, LaserPathEvent, LaserPlugin, LaserStrength,
+        Position, ShieldHitEvent, Shooter, Splitting, YReflection,
+    };
+
+    // Final strength of every beam after a laser of the given strength crosses a splitter
+    fn split_strengths(strength: usize) -> Vec<usize> {
+        let mut app = App::new();
+        app.add_event::<LaserPathEvent>()
+            .add_event::<LaserHitEvent>()
+            .add_event::<ShieldHitEvent>()
+            .add_event::<ActionCompleteEvent>()
+            .add_systems(Update, LaserPlugin::track_lasers);
+
+        let world = app.world_mut();
+        world.spawn((
+            GamePhase::Act,
+            MapSize {
+                half_width: 3,
+                half_height: 3,
+            },
+        ));
+        let shooter = world.spawn_empty().id();
+        world.spawn((
+            Laser,
+            Position::from(Hex::ORIGIN),
+            Direction::North,
+            Shooter::new(shooter),
+            LaserStrength::new(strength),
+        ));
+        let splitter = world.spawn_empty().id();
+        world.spawn((
+            Splitting::new(Direction::North),
+            Consumption::passing_bundle(
+                splitter,
+                Vec::new(),
+                Position::from(Hex::ORIGIN.neighbor(Direction::North.as_hex())),
+            ),
+        ));
+
+        app.update();
+
+        let paths = app.world().resource::<Events<LaserPathEvent>>();
+        paths
+            .get_reader()
+            .read(paths)
+            .map(|path| *path.strengths.last().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_split_shares_strength() {
+        assert_eq!(split_strengths(3), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_base_strength_laser_does_not_split() {
+        assert_eq!(split_strengths(Laser::POWER), vec![Laser::POWER]);
+    }
+
+    #[test]
+    fn test_left_tilt_reflect() {
+        let cases = [
+            (Direction::North, Direction::Northwest),
+            (Direction::South, Direction::Southwest),
+            (Direction::Northeast, Direction::Southeast),
+            (Direction::Southeast, Direction::Northeast),
+            (Direction::Northwest, Direction::North),
+            (Direction::Southwest, Direction::South),
+        ];
+        for (incoming, outgoing) in cases {
+            assert_eq!(YReflection::LeftTilt.reflect(incoming), outgoing);
+        }
+    }
+
+    #[test]
+    fn test_right_tilt_reflect() {
+        let cases = [
+            (Direction::North, Direction::Northeast),
+            (Direction::South, Direction::Southeast),
+            (Direction::Northeast, Direction::North),
+            (Direction::Southeast, Direction::South),
+            (Direction::Northwest, Direction::Southwest),
+            (Direction::Southwest, Direction::Northwest),
+        ];
+        for (incoming, outgoing) in cases {
+            assert_eq!(YReflection::RightTilt.reflect(incoming), outgoing);
+        }
+    }
+
+    #[test]
+    fn test_toggled_tilt_reflects_differently() {
+        for incoming in Direction::ALL {
+            assert_ne!(
+                YReflection::LeftTilt.reflect(incoming),
+                YReflection::LeftTilt.toggled().reflect(incoming)
+            );
+        }
     }
 }
@@ -11,13 +11,14 @@ use bevy::{
 };
 use hexx::{shapes, Hex, HexLayout};
 use noise::{utils::*, Fbm, MultiFractal, Perlin};
-use rand::{seq::IteratorRandom, Rng};
+use rand::{distributions::WeightedIndex, prelude::Distribution, seq::IteratorRandom, Rng};
 
 use entropy::EntropyBundle;
 use game_loop::{GameInstance, GamePlayers, MapSize};
 use hq::HQTile;
 use mountain::MountainTile;
 use resource_deposit::ResourceDepositTile;
+use terrain::{CrystalTile, FogTile, WaterTile};
 use tilemap::{
     EmptyTile, EmptyTileMaterial, Tile, TileBundle, Tilemap, TilemapEntities, TilemapLayout,
     TilemapPlugin,
@@ -41,6 +42,17 @@ impl MapGeneratorPlugin {
     pub const TILE_CUTOFF: f64 = 0.4;
     pub const OBSTACLE_CUTOFF: f64 = 0.7;
 
+    // Relative chance of each obstacle kind being picked for a generated obstacle tile
+    pub fn terrain_weights() -> [(TypeId, u32); 5] {
+        [
+            (TypeId::of::<MountainTile>(), 45),
+            (TypeId::of::<ResourceDepositTile>(), 25),
+            (TypeId::of::<WaterTile>(), 15),
+            (TypeId::of::<FogTile>(), 10),
+            (TypeId::of::<CrystalTile>(), 5),
+        ]
+    }
+
     fn spawn_map(
         mut games: Query<
             (Entity, &MapSize, &mut EntropyBundle),
@@ -90,7 +102,7 @@ impl MapGeneratorPlugin {
                         TileBundle::new(
                             Tile::from(coord),
                             position,
-                            10.,
+                            TilemapPlugin::TILE_Z,
                             tile_mesh.clone(),
                             empty_tile_material.clone_weak(),
                         ),
@@ -218,18 +230,21 @@ impl MapGeneratorPlugin {
                 obstacle_map.remove(&tile);
             }
 
+            let weights = Self::terrain_weights();
+            let Ok(distribution) = WeightedIndex::new(weights.iter().map(|(_, weight)| weight))
+            else {
+                info!("Terrain weights do not form a valid distribution");
+                return;
+            };
+
             for tile in obstacle_map.iter() {
-                let sample: f32 = entropy.entropy.gen_range(0.0..=1.0);
+                let (tile_id, _) = weights[distribution.sample(&mut entropy.entropy)];
                 let Some(tile_entity) = tilemap.tiles.get(&(**tile)) else {
                     info!("Tile for spawned obstacle not found within the tilemap");
                     return;
                 };
                 tile_spawns.send(TileSpawnEvent {
-                    tile_id: if sample > 0.25 {
-                        TypeId::of::<MountainTile>()
-                    } else {
-                        TypeId::of::<ResourceDepositTile>()
-                    },
+                    tile_id,
                     on_tile: *tile_entity,
                     owner: game,
                     game: game,
@@ -0,0 +1,325 @@
+use bevy::prelude::*;
+
+use game_loop::{GamePhase, InGame};
+use tilemap::EmptyTile;
+use tiles::{
+    lasers::{Direction, Position, Rotation, YReflection},
+    Level, Owner, TileOccupancy,
+};
+
+use crate::{
+    ActionHistory, HistoryAction, Merch, MerchRegistry, MerchStock, Money, MoneyChangeReason,
+    MoneyChanged, Orientation, PurchaseError, PurchaseFailed, Shopper,
+};
+
+// Sells the tower a player owns on the given map tile back to the shop
+#[derive(Clone, Copy, Debug)]
+#[derive(Event, Reflect)]
+pub struct Sell {
+    pub seller: Entity,
+    pub tile: Entity,
+}
+
+// Raises the tower a player owns on the given map tile to the requested level
+#[derive(Clone, Copy, Debug)]
+#[derive(Event, Reflect)]
+pub struct Upgrade {
+    pub buyer: Entity,
+    pub tile: Entity,
+    pub level: Level,
+}
+
+// Changes the direction or rotation of a tower bought in an earlier turn
+#[derive(Clone, Copy, Debug)]
+#[derive(Event, Reflect)]
+pub struct Reorient {
+    pub player: Entity,
+    pub tile: Entity,
+    pub direction: Option<Direction>,
+    pub rotation: Option<Rotation>,
+    pub tilt: Option<YReflection>,
+}
+
+impl Reorient {
+    pub const FEE: Money = Money::new(1);
+}
+
+// Sent once a sold tower has been removed so that the seller can be refunded
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct TowerSold {
+    pub seller: Entity,
+    pub merch: Merch,
+    pub level: Level,
+    pub game: Entity,
+}
+
+// Finds the tower standing on a map tile and checks that the player owns it
+fn owned_tower(
+    player: Entity,
+    tile: Entity,
+    hexes: &Query<&tilemap::Tile>,
+    towers: &Query<(Entity, &Position, &Owner, &Merch, Option<&Level>)>,
+) -> Result<(Entity, Merch, Level), PurchaseError> {
+    let Ok(hex) = hexes.get(tile) else {
+        return Err(PurchaseError::NotForSale {
+            tile,
+            coordinates: IVec2::ZERO,
+        });
+    };
+    let coordinates = IVec2::new(hex.x, hex.y);
+    let Some((tower, _, owner, merch, level)) = towers
+        .iter()
+        .find(|(_, position, _, _, _)| ***position == **hex)
+    else {
+        return Err(PurchaseError::NotForSale { tile, coordinates });
+    };
+    if **owner != player {
+        return Err(PurchaseError::NotOwned {
+            tile,
+            coordinates,
+            name: merch.name().to_string(),
+        });
+    }
+    Ok((tower, merch.clone(), level.copied().unwrap_or_default()))
+}
+
+// Towers are only changed while choosing, never while lasers are being traced or drawn
+fn check_phase(
+    tower: Entity,
+    tile: Entity,
+    action: &str,
+    games: &Query<&InGame>,
+    phases: &Query<&GamePhase>,
+) -> Result<(), PurchaseError> {
+    let choosing = games
+        .get(tower)
+        .and_then(|game| phases.get(**game))
+        .is_ok_and(|phase| matches!(phase, GamePhase::Choose));
+    if choosing {
+        Ok(())
+    } else {
+        Err(PurchaseError::WrongPhase {
+            tile,
+            action: action.to_string(),
+        })
+    }
+}
+
+// Marks the map tile of a removed tower as empty, unless another tower is stacked on it
+pub(crate) fn release_tile(
+    commands: &mut Commands,
+    tile: Entity,
+    tower: Entity,
+    game: Entity,
+    hexes: &Query<&tilemap::Tile>,
+    occupancies: &Query<&TileOccupancy>,
+) {
+    let shared = hexes.get(tile).is_ok_and(|hex| {
+        occupancies
+            .get(game)
+            .is_ok_and(|occupancy| occupancy.occupants(hex).any(|occupant| occupant != tower))
+    });
+    if !shared {
+        commands.entity(tile).insert(EmptyTile);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn handle_sales(
+    mut commands: Commands,
+    mut sales: EventReader<Sell>,
+    mut sold: EventWriter<TowerSold>,
+    mut failures: EventWriter<PurchaseFailed>,
+    hexes: Query<&tilemap::Tile>,
+    towers: Query<(Entity, &Position, &Owner, &Merch, Option<&Level>)>,
+    games: Query<&InGame>,
+    phases: Query<&GamePhase>,
+    occupancies: Query<&TileOccupancy>,
+    mut stocks: Query<&mut MerchStock>,
+) {
+    for Sell { seller, tile } in sales.read() {
+        let (tower, merch, level) = match owned_tower(*seller, *tile, &hexes, &towers)
+            .and_then(|tower| check_phase(tower.0, *tile, "sold", &games, &phases).map(|_| tower))
+        {
+            Ok(tower) => tower,
+            Err(error) => {
+                failures.send(PurchaseFailed::new(*seller, error));
+                continue;
+            }
+        };
+        let Ok(game) = games.get(tower) else {
+            continue;
+        };
+
+        info!("Selling {} on tile {:?}", merch.name(), tile);
+        commands.entity(tower).despawn_recursive();
+        if let Ok(mut stock) = stocks.get_mut(**game) {
+            stock.give_back(&merch.id());
+        }
+        release_tile(&mut commands, *tile, tower, **game, &hexes, &occupancies);
+        sold.send(TowerSold {
+            seller: *seller,
+            merch,
+            level,
+            game: **game,
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn handle_upgrades(
+    mut commands: Commands,
+    mut upgrades: EventReader<Upgrade>,
+    mut money_changes: EventWriter<MoneyChanged>,
+    mut failures: EventWriter<PurchaseFailed>,
+    hexes: Query<&tilemap::Tile>,
+    towers: Query<(Entity, &Position, &Owner, &Merch, Option<&Level>)>,
+    games: Query<&InGame>,
+    phases: Query<&GamePhase>,
+    registry: Res<MerchRegistry>,
+    mut shoppers: Query<&mut Money, With<Shopper>>,
+) {
+    for Upgrade { buyer, tile, level } in upgrades.read() {
+        let (tower, merch, current) =
+            match owned_tower(*buyer, *tile, &hexes, &towers).and_then(|tower| {
+                check_phase(tower.0, *tile, "upgraded", &games, &phases).map(|_| tower)
+            }) {
+                Ok(tower) => tower,
+                Err(error) => {
+                    failures.send(PurchaseFailed::new(*buyer, error));
+                    continue;
+                }
+            };
+        // Towers that ignore their level would be paid for without getting anything
+        if !registry.is_upgradable(&merch.id()) || *level <= current || *level > Level::MAX {
+            failures.send(PurchaseFailed::new(
+                *buyer,
+                PurchaseError::InvalidUpgrade {
+                    tile: *tile,
+                    name: merch.name().to_string(),
+                    current,
+                    requested: *level,
+                },
+            ));
+            continue;
+        }
+        let Ok(mut money) = shoppers.get_mut(*buyer) else {
+            continue;
+        };
+
+        // Each level costs the same as buying the tower again at its current price
+        let Some(price) = registry.price(&merch.id()) else {
+            failures.send(PurchaseFailed::new(
+                *buyer,
+                PurchaseError::UnknownMerch {
+                    merch_id: merch.id(),
+                    name: merch.name().to_string(),
+                },
+            ));
+            continue;
+        };
+        let cost = Money::new(*price * (**level - *current) as usize);
+        if *money < cost {
+            failures.send(PurchaseFailed::new(
+                *buyer,
+                PurchaseError::NotEnoughMoney {
+                    item: format!("{} level {}", merch.name(), level),
+                    cost,
+                    money: *money,
+                },
+            ));
+            continue;
+        }
+
+        **money -= *cost;
+        money_changes.send(MoneyChanged {
+            player: *buyer,
+            delta: -(*cost as isize),
+            reason: MoneyChangeReason::Upgrade(merch.id()),
+        });
+        info!("Upgraded {} to level {}", merch.name(), **level);
+        commands.entity(tower).insert(*level);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn handle_reorients(
+    mut reorients: EventReader<Reorient>,
+    mut money_changes: EventWriter<MoneyChanged>,
+    mut failures: EventWriter<PurchaseFailed>,
+    hexes: Query<&tilemap::Tile>,
+    towers: Query<(Entity, &Position, &Owner, &Merch, Option<&Level>)>,
+    mut orientations: Query<(
+        Option<&mut Direction>,
+        Option<&mut Rotation>,
+        Option<&mut YReflection>,
+    )>,
+    games: Query<&InGame>,
+    phases: Query<&GamePhase>,
+    mut shoppers: Query<(&mut Money, &mut ActionHistory), With<Shopper>>,
+) {
+    for Reorient {
+        player,
+        tile,
+        direction,
+        rotation,
+        tilt,
+    } in reorients.read()
+    {
+        let (tower, merch, _) =
+            match owned_tower(*player, *tile, &hexes, &towers).and_then(|tower| {
+                check_phase(tower.0, *tile, "reoriented", &games, &phases).map(|_| tower)
+            }) {
+                Ok(tower) => tower,
+                Err(error) => {
+                    failures.send(PurchaseFailed::new(*player, error));
+                    continue;
+                }
+            };
+        let Ok((mut money, mut history)) = shoppers.get_mut(*player) else {
+            continue;
+        };
+        if *money < Reorient::FEE {
+            failures.send(PurchaseFailed::new(
+                *player,
+                PurchaseError::NotEnoughMoney {
+                    item: format!("reorienting {}", merch.name()),
+                    cost: Reorient::FEE,
+                    money: *money,
+                },
+            ));
+            continue;
+        }
+        let Ok((current_direction, current_rotation, current_tilt)) = orientations.get_mut(tower)
+        else {
+            continue;
+        };
+
+        let from = Orientation::new(
+            current_direction.as_deref(),
+            current_rotation.as_deref(),
+            current_tilt.as_deref(),
+        );
+        let to = Orientation {
+            direction: from.direction.map(|current| direction.unwrap_or(current)),
+            rotation: from.rotation.map(|current| rotation.unwrap_or(current)),
+            tilt: from.tilt.map(|current| tilt.unwrap_or(current)),
+        };
+        to.apply(current_direction, current_rotation, current_tilt);
+        if to != from {
+            history.record(HistoryAction::Reorient {
+                tower,
+                from,
+                to,
+                fee: Reorient::FEE,
+            });
+            **money -= *Reorient::FEE;
+            money_changes.send(MoneyChanged {
+                player: *player,
+                delta: -(*Reorient::FEE as isize),
+                reason: MoneyChangeReason::Reorient,
+            });
+        }
+    }
+}
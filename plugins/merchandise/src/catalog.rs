@@ -0,0 +1,107 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use thiserror::Error;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+};
+
+use crate::MerchRegistry;
+
+// Overrides for registered merch, keyed by `Merchandise::KEY`, e.g. `laser_tower`. Anything left
+// out keeps the values from its `Merchandise` impl
+#[derive(Clone, Debug, Default)]
+#[derive(Asset, Deserialize, Reflect)]
+pub struct MerchCatalog {
+    #[serde(default)]
+    pub merch: BTreeMap<String, CatalogEntry>,
+}
+
+impl MerchCatalog {
+    pub const DEFAULT_PATH: &'static str = "merch.catalog.ron";
+}
+
+#[derive(Clone, Debug, Default)]
+#[derive(Deserialize, Reflect)]
+#[serde(default)]
+pub struct CatalogEntry {
+    pub name: Option<String>,
+    pub price: Option<usize>,
+    pub stock: Option<usize>,
+    pub player_cap: Option<usize>,
+    pub unlock_turn: usize,
+}
+
+// The catalog in use, insert it before startup to load a different catalog for a game mode
+#[derive(Clone, Debug)]
+#[derive(Deref, Resource)]
+pub struct MerchCatalogHandle(pub Handle<MerchCatalog>);
+
+#[derive(Default)]
+pub struct MerchCatalogLoader;
+
+#[derive(Debug)]
+#[derive(Error)]
+pub enum MerchCatalogError {
+    #[error("Could not read merch catalog: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Could not parse merch catalog: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for MerchCatalogLoader {
+    type Asset = MerchCatalog;
+    type Settings = ();
+    type Error = MerchCatalogError;
+
+    async fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader<'_>,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext<'_>,
+    ) -> Result<MerchCatalog, MerchCatalogError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        Ok(ron::de::from_bytes(&bytes)?)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["catalog.ron"]
+    }
+}
+
+pub(crate) fn load_catalog(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    handle: Option<Res<MerchCatalogHandle>>,
+) {
+    if handle.is_none() {
+        commands.insert_resource(MerchCatalogHandle(
+            asset_server.load(MerchCatalog::DEFAULT_PATH),
+        ));
+    }
+}
+
+// Applied whenever the catalog finishes loading or is hot reloaded
+pub(crate) fn apply_catalog(
+    mut events: EventReader<AssetEvent<MerchCatalog>>,
+    handle: Res<MerchCatalogHandle>,
+    catalogs: Res<Assets<MerchCatalog>>,
+    mut registry: ResMut<MerchRegistry>,
+) {
+    for event in events.read() {
+        let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+        else {
+            continue;
+        };
+        if *id != handle.id() {
+            continue;
+        }
+        if let Some(catalog) = catalogs.get(*id) {
+            info!("Applying merch catalog");
+            registry.apply_catalog(catalog);
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use bevy::prelude::*;
+use std::borrow::Cow;
+
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::MerchLimits;
 
 #[derive(Clone, Copy, Debug, Default)]
 #[derive(Component, Reflect)]
@@ -20,32 +24,102 @@ impl Money {
     }
 }
 
+// A 64-bit FNV-1a hash of the merch key, so ids are the same on every build and client
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[derive(Component, Deref, DerefMut, Reflect)]
-pub struct MerchId(usize);
+#[derive(Component, Deref, Reflect)]
+pub struct MerchId(u64);
+
+impl MerchId {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    pub const fn from_key(key: &str) -> Self {
+        let bytes = key.as_bytes();
+        let mut hash = Self::FNV_OFFSET;
+        let mut index = 0;
+        while index < bytes.len() {
+            hash ^= bytes[index] as u64;
+            hash = hash.wrapping_mul(Self::FNV_PRIME);
+            index += 1;
+        }
+        MerchId(hash)
+    }
+}
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[derive(Component, Reflect)]
 pub struct Merch {
     id: MerchId,
+    key: &'static str,
     price: Money,
-    name: &'static str,
+    name: Cow<'static, str>,
 }
 
 impl Merch {
-    pub const fn new(id: MerchId, name: &'static str, price: Money) -> Self {
-        Merch { id, name, price }
+    pub const fn new(key: &'static str, name: &'static str, price: Money) -> Self {
+        Merch {
+            id: MerchId::from_key(key),
+            key,
+            name: Cow::Borrowed(name),
+            price,
+        }
     }
 
     pub fn id(&self) -> MerchId {
         self.id
     }
 
-    pub fn name(&self) -> &'static str {
-        self.name
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
     pub fn price(&self) -> Money {
         self.price
     }
+
+    pub(crate) fn set_name(&mut self, name: impl Into<Cow<'static, str>>) {
+        self.name = name.into();
+    }
+
+    pub(crate) fn set_price(&mut self, price: Money) {
+        self.price = price;
+    }
+}
+
+// How many copies of each merch have been taken out of a game's stock, kept per game so that
+// concurrent games and catalog reloads do not share or refill it
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Deref, DerefMut, Reflect)]
+pub struct MerchStock(HashMap<MerchId, usize>);
+
+impl MerchStock {
+    pub fn remaining(&self, merch: &MerchId, limits: &MerchLimits) -> Option<usize> {
+        let taken = self.get(merch).copied().unwrap_or_default();
+        limits.stock.map(|stock| stock.saturating_sub(taken))
+    }
+
+    // Takes one copy out of the stock, returning false if the merch is sold out
+    pub fn take(&mut self, merch: &MerchId, limits: &MerchLimits) -> bool {
+        if self.remaining(merch, limits) == Some(0) {
+            return false;
+        }
+        *self.entry(*merch).or_default() += 1;
+        true
+    }
+
+    // Puts back a copy that was sold or whose purchase was undone
+    pub fn give_back(&mut self, merch: &MerchId) {
+        if let Some(taken) = self.get_mut(merch) {
+            *taken = taken.saturating_sub(1);
+        }
+    }
 }
+
+// How many of each merch a shopper has bought, used to enforce per-player purchase caps
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Deref, DerefMut, Reflect)]
+pub struct PurchaseCounts(HashMap<MerchId, usize>);
@@ -0,0 +1,285 @@
+use bevy::prelude::*;
+
+use game_loop::{GamePhase, GamePlayers, InGame};
+use tiles::{
+    lasers::{Direction, Position, Rotation, YReflection},
+    Level, Owner, TileOccupancy,
+};
+
+use crate::{
+    release_tile, Merch, MerchStock, Money, MoneyChangeReason, MoneyChanged, Purchase,
+    PurchaseCounts, PurchaseError, PurchaseFailed, Shopper,
+};
+
+// The direction, rotation and tilt of a tower, `None` for whichever the tower does not have
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Reflect)]
+pub struct Orientation {
+    pub direction: Option<Direction>,
+    pub rotation: Option<Rotation>,
+    pub tilt: Option<YReflection>,
+}
+
+impl Orientation {
+    pub fn new(
+        direction: Option<&Direction>,
+        rotation: Option<&Rotation>,
+        tilt: Option<&YReflection>,
+    ) -> Self {
+        Orientation {
+            direction: direction.copied(),
+            rotation: rotation.copied(),
+            tilt: tilt.copied(),
+        }
+    }
+
+    pub(crate) fn apply(
+        &self,
+        direction: Option<Mut<Direction>>,
+        rotation: Option<Mut<Rotation>>,
+        tilt: Option<Mut<YReflection>>,
+    ) {
+        if let (Some(mut current), Some(direction)) = (direction, self.direction) {
+            *current = direction;
+        }
+        if let (Some(mut current), Some(rotation)) = (rotation, self.rotation) {
+            *current = rotation;
+        }
+        if let (Some(mut current), Some(tilt)) = (tilt, self.tilt) {
+            *current = tilt;
+        }
+    }
+}
+
+// A choose-phase action that can be taken back until the phase moves to act
+#[derive(Clone, Debug)]
+#[derive(Reflect)]
+pub enum HistoryAction {
+    Purchase {
+        merch: Merch,
+        tile: Entity,
+        cost: Money,
+    },
+    Reorient {
+        tower: Entity,
+        from: Orientation,
+        to: Orientation,
+        fee: Money,
+    },
+}
+
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Reflect)]
+pub struct ActionHistory {
+    undo: Vec<HistoryAction>,
+    redo: Vec<HistoryAction>,
+}
+
+impl ActionHistory {
+    // A new action makes anything that was undone before it impossible to redo
+    pub fn record(&mut self, action: HistoryAction) {
+        self.undo.push(action);
+        self.redo.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+
+    // Redone purchases go through `handle_purchases` again and must keep the rest of the redo
+    // stack intact
+    pub(crate) fn push_redone(&mut self, action: HistoryAction) {
+        self.undo.push(action);
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Event, Reflect)]
+pub struct Undo {
+    pub player: Entity,
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Event, Reflect)]
+pub struct Redo {
+    pub player: Entity,
+}
+
+fn is_choosing(player: Entity, players: &Query<&InGame>, games: &Query<&GamePhase>) -> bool {
+    players
+        .get(player)
+        .ok()
+        .and_then(|game| games.get(**game).ok())
+        .is_some_and(|phase| matches!(phase, GamePhase::Choose))
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn handle_undo(
+    mut commands: Commands,
+    mut undos: EventReader<Undo>,
+    mut money_changes: EventWriter<MoneyChanged>,
+    mut stocks: Query<&mut MerchStock>,
+    mut shoppers: Query<(&mut Money, &mut PurchaseCounts, &mut ActionHistory), With<Shopper>>,
+    players: Query<&InGame>,
+    games: Query<&GamePhase>,
+    hexes: Query<&tilemap::Tile>,
+    towers: Query<(Entity, &Position, &Owner, &Merch, Option<&Level>)>,
+    mut orientations: Query<(
+        Option<&mut Direction>,
+        Option<&mut Rotation>,
+        Option<&mut YReflection>,
+    )>,
+    occupancies: Query<&TileOccupancy>,
+) {
+    for Undo { player } in undos.read() {
+        if !is_choosing(*player, &players, &games) {
+            continue;
+        }
+        let Ok((mut money, mut counts, mut history)) = shoppers.get_mut(*player) else {
+            continue;
+        };
+        let Some(action) = history.undo.pop() else {
+            continue;
+        };
+
+        let refund = match &action {
+            HistoryAction::Purchase { merch, tile, cost } => {
+                let Ok(hex) = hexes.get(*tile) else {
+                    continue;
+                };
+                // A tower that has been sold since was already refunded
+                let Some((tower, _, _, _, level)) =
+                    towers.iter().find(|(_, position, owner, tower_merch, _)| {
+                        ***position == **hex
+                            && ***owner == *player
+                            && tower_merch.id() == merch.id()
+                    })
+                else {
+                    continue;
+                };
+                info!("Undoing purchase of {} on tile {:?}", merch.name(), tile);
+                commands.entity(tower).despawn_recursive();
+                if let Ok(game) = players.get(*player) {
+                    release_tile(&mut commands, *tile, tower, **game, &hexes, &occupancies);
+                    if let Ok(mut stock) = stocks.get_mut(**game) {
+                        stock.give_back(&merch.id());
+                    }
+                }
+                if let Some(count) = counts.get_mut(&merch.id()) {
+                    *count = count.saturating_sub(1);
+                }
+                // Upgrades bought on top of the purchase are refunded as well
+                Money::new(**cost * *level.copied().unwrap_or_default() as usize)
+            }
+            HistoryAction::Reorient {
+                tower, from, fee, ..
+            } => {
+                let Ok((direction, rotation, tilt)) = orientations.get_mut(*tower) else {
+                    continue;
+                };
+                info!("Undoing reorientation of {:?}", tower);
+                from.apply(direction, rotation, tilt);
+                *fee
+            }
+        };
+
+        **money += *refund;
+        if *refund > 0 {
+            money_changes.send(MoneyChanged {
+                player: *player,
+                delta: *refund as isize,
+                reason: MoneyChangeReason::Refund,
+            });
+        }
+        history.redo.push(action);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn handle_redo(
+    mut redos: EventReader<Redo>,
+    mut purchases: EventWriter<Purchase>,
+    mut money_changes: EventWriter<MoneyChanged>,
+    mut failures: EventWriter<PurchaseFailed>,
+    mut shoppers: Query<(&mut Money, &mut ActionHistory), With<Shopper>>,
+    players: Query<&InGame>,
+    games: Query<&GamePhase>,
+    mut orientations: Query<(
+        Option<&mut Direction>,
+        Option<&mut Rotation>,
+        Option<&mut YReflection>,
+    )>,
+) {
+    for Redo { player } in redos.read() {
+        if !is_choosing(*player, &players, &games) {
+            continue;
+        }
+        let Ok((mut money, mut history)) = shoppers.get_mut(*player) else {
+            continue;
+        };
+        let Some(action) = history.redo.pop() else {
+            continue;
+        };
+
+        match &action {
+            HistoryAction::Purchase { merch, tile, .. } => {
+                info!("Redoing purchase of {} on tile {:?}", merch.name(), tile);
+                purchases.send(Purchase::redo(*player, merch.clone(), *tile));
+            }
+            HistoryAction::Reorient { tower, to, fee, .. } => {
+                if *money < *fee {
+                    failures.send(PurchaseFailed::new(
+                        *player,
+                        PurchaseError::NotEnoughMoney {
+                            item: "redoing a reorientation".to_string(),
+                            cost: *fee,
+                            money: *money,
+                        },
+                    ));
+                    history.redo.push(action);
+                    continue;
+                }
+                let Ok((direction, rotation, tilt)) = orientations.get_mut(*tower) else {
+                    continue;
+                };
+                info!("Redoing reorientation of {:?}", tower);
+                to.apply(direction, rotation, tilt);
+                **money -= **fee;
+                if **fee > 0 {
+                    money_changes.send(MoneyChanged {
+                        player: *player,
+                        delta: -(**fee as isize),
+                        reason: MoneyChangeReason::Reorient,
+                    });
+                }
+                history.push_redone(action);
+            }
+        }
+    }
+}
+
+// Nothing chosen in a turn can be taken back once it is being played out
+pub(crate) fn clear_histories(
+    games: Query<(&GamePhase, &GamePlayers), Changed<GamePhase>>,
+    mut histories: Query<&mut ActionHistory>,
+) {
+    for (_, players) in games
+        .iter()
+        .filter(|(phase, _)| matches!(phase, GamePhase::Act))
+    {
+        for player in players.iter() {
+            if let Ok(mut history) = histories.get_mut(*player) {
+                history.clear();
+            }
+        }
+    }
+}
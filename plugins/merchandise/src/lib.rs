@@ -1,20 +1,33 @@
 use thiserror::Error;
 
-use bevy::{prelude::*, reflect::GetTypeRegistration, utils::HashMap};
+use bevy::{
+    prelude::*,
+    reflect::GetTypeRegistration,
+    utils::{HashMap, HashSet},
+};
 
-use bevy_anyhow_alert::*;
-
-use game_loop::{GameInstance, GamePlayers, Player};
-use tiles::{Territory, TileSpawnEvent};
+use game_loop::{GameInstance, GamePlayers, Player, Turn};
+use tiles::{Level, Owner, StackableTiles, Territory, TileOccupancy, TileSpawnEvent};
 
+mod actions;
+pub use actions::*;
+mod catalog;
+pub use catalog::*;
 mod components;
 pub use components::*;
+mod history;
+pub use history::*;
 mod registry;
 pub use registry::*;
 
 pub trait Merchandise {
+    // Stable identifier used to derive the `MerchId` and to match catalog entries, must never
+    // change once released
+    const KEY: &'static str;
     const PRICE: Money;
     const NAME: &'static str;
+    // Only merch whose tiles read their `Level` can be upgraded
+    const UPGRADABLE: bool = false;
 
     fn material(asset_server: &AssetServer) -> ColorMaterial;
 }
@@ -24,11 +37,34 @@ pub struct MerchPlugin;
 impl Plugin for MerchPlugin {
     fn build(&self, app: &mut App) {
         app.add_event::<Purchase>();
+        app.add_event::<MoneyChanged>();
+        app.add_event::<PurchaseFailed>();
+        app.add_event::<Sell>();
+        app.add_event::<Upgrade>();
+        app.add_event::<Reorient>();
+        app.add_event::<TowerSold>();
+        app.add_event::<Undo>();
+        app.add_event::<Redo>();
         app.init_resource::<MerchRegistry>();
         app.init_resource::<MerchMaterials>();
+        app.init_asset::<MerchCatalog>();
+        app.init_asset_loader::<MerchCatalogLoader>();
+        app.add_systems(Startup, load_catalog);
         app.add_systems(
             Update,
-            (Self::spawn_shoppers, Self::handle_purchases.anyhow_alerts()).in_set(MerchSystems),
+            (
+                apply_catalog.run_if(resource_exists::<MerchCatalogHandle>),
+                Self::spawn_shoppers,
+                Self::add_stock,
+                handle_undo,
+                handle_redo,
+                Self::handle_purchases,
+                handle_sales,
+                handle_upgrades,
+                handle_reorients,
+                clear_histories,
+            )
+                .in_set(MerchSystems),
         );
     }
 }
@@ -36,76 +72,190 @@ impl Plugin for MerchPlugin {
 impl MerchPlugin {
     fn spawn_shoppers(mut commands: Commands, added_players: Query<Entity, Added<Player>>) {
         for player in &added_players {
-            commands.entity(player).insert((Shopper, Money::new(50)));
+            commands.entity(player).insert((
+                Shopper,
+                Money::new(50),
+                PurchaseCounts::default(),
+                ActionHistory::default(),
+            ));
+        }
+    }
+
+    fn add_stock(
+        mut commands: Commands,
+        games: Query<Entity, (With<GameInstance>, Without<MerchStock>)>,
+    ) {
+        for game in &games {
+            commands.entity(game).insert(MerchStock::default());
         }
     }
 
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
     fn handle_purchases(
         mut purchases: EventReader<Purchase>,
         mut tile_spawns: EventWriter<TileSpawnEvent>,
+        mut money_changes: EventWriter<MoneyChanged>,
+        mut failures: EventWriter<PurchaseFailed>,
         registry: Res<MerchRegistry>,
-        mut shoppers: Query<(&mut Money, &Territory), With<Shopper>>,
-        games: Query<(Entity, &GamePlayers), With<GameInstance>>,
-    ) -> ResultVec<(), PurchaseError> {
-        let mut errors = vec![];
+        mut shoppers: Query<
+            (
+                &mut Money,
+                &mut PurchaseCounts,
+                &mut ActionHistory,
+                &Territory,
+            ),
+            With<Shopper>,
+        >,
+        mut games: Query<
+            (
+                Entity,
+                &GamePlayers,
+                &Turn,
+                &mut MerchStock,
+                Option<&TileOccupancy>,
+            ),
+            With<GameInstance>,
+        >,
+        hexes: Query<&tilemap::Tile>,
+        stackable: Res<StackableTiles>,
+    ) {
+        // Tiles bought earlier in this frame are still marked empty until the spawn is applied
+        let mut claimed = HashSet::new();
         for Purchase {
             buyer,
             merch,
             on_tile,
+            redo,
         } in purchases.read()
         {
             info!("Handling purchase");
-            let Ok((mut money, territory)) = shoppers.get_mut(*buyer) else {
+            let Ok((mut money, mut counts, mut history, territory)) = shoppers.get_mut(*buyer)
+            else {
                 continue;
             };
 
-            let Some((game, _)) = games
-                .iter()
-                .filter(|(_, players)| players.contains(buyer))
-                .next()
+            let Some((game, _, turn, mut stock, occupancy)) = games
+                .iter_mut()
+                .find(|(_, players, ..)| players.contains(buyer))
             else {
                 continue;
             };
+            let Ok(hex) = hexes.get(*on_tile) else {
+                continue;
+            };
+            let coordinates = IVec2::new(hex.x, hex.y);
 
             info!("Player recognized");
-            let cost = merch.price();
-            if **money >= *cost {
-                if let Some(tile_id) = registry.get_type(&merch.id()) {
-                    if territory.contains(on_tile) {
-                        **money = money.saturating_sub(*cost);
-                        info!(
-                            "Tile spawn event on tile {:?} for tile type {:?}",
-                            *on_tile, *tile_id
-                        );
-                        tile_spawns.send(TileSpawnEvent {
-                            tile_id: *tile_id,
-                            on_tile: *on_tile,
-                            owner: *buyer,
-                            game,
-                        });
-                    } else {
-                        info!("Cannot purchase on tile outside of territory");
-                        errors.push(PurchaseError::UncontrolledTile { tile: *on_tile })
-                    }
-                } else {
-                    info!("Unknown merch error");
-                    errors.push(PurchaseError::UnknownMerch {
+            let (Some(tile_id), Some(cost)) = (
+                registry.get_type(&merch.id()).copied(),
+                registry.price(&merch.id()),
+            ) else {
+                info!("Unknown merch error");
+                failures.send(PurchaseFailed::new(
+                    *buyer,
+                    PurchaseError::UnknownMerch {
                         merch_id: merch.id(),
-                    })
-                }
-            } else {
-                info!("Insufficient funds purchase error");
-                errors.push(PurchaseError::NotEnoughMoney {
-                    shopper: *buyer,
+                        name: merch.name().to_string(),
+                    },
+                ));
+                continue;
+            };
+            let name = registry
+                .get_by_key(merch.key())
+                .map_or(merch.name(), Merch::name)
+                .to_string();
+            let limits = registry.limits(&merch.id());
+            let bought = counts.get(&merch.id()).copied().unwrap_or_default();
+
+            let error = if **turn < limits.unlock_turn {
+                Some(PurchaseError::Locked {
+                    merch_id: merch.id(),
+                    name,
+                    unlock_turn: limits.unlock_turn,
+                })
+            } else if let Some(cap) = limits.player_cap.filter(|cap| bought >= *cap) {
+                Some(PurchaseError::PurchaseCapReached {
+                    merch_id: merch.id(),
+                    name,
+                    cap,
+                })
+            } else if **money < *cost {
+                Some(PurchaseError::NotEnoughMoney {
+                    item: name,
                     cost,
                     money: *money,
                 })
+            } else if !territory.contains(on_tile) {
+                Some(PurchaseError::UncontrolledTile {
+                    tile: *on_tile,
+                    coordinates,
+                })
+            } else if claimed.contains(on_tile)
+                || occupancy.is_some_and(|occupancy| !occupancy.can_place(hex, tile_id, &stackable))
+            {
+                Some(PurchaseError::Occupied {
+                    tile: *on_tile,
+                    coordinates,
+                })
+            } else if !stock.take(&merch.id(), &limits) {
+                Some(PurchaseError::SoldOut {
+                    merch_id: merch.id(),
+                    name,
+                })
+            } else {
+                None
+            };
+            if let Some(error) = error {
+                info!("Purchase failed: {}", error);
+                failures.send(PurchaseFailed::new(*buyer, error));
+                continue;
+            }
+
+            **money = money.saturating_sub(*cost);
+            *counts.entry(merch.id()).or_default() += 1;
+            claimed.insert(*on_tile);
+            let action = HistoryAction::Purchase {
+                merch: merch.clone(),
+                tile: *on_tile,
+                cost,
+            };
+            if *redo {
+                history.push_redone(action);
+            } else {
+                history.record(action);
             }
+            money_changes.send(MoneyChanged {
+                player: *buyer,
+                delta: -(*cost as isize),
+                reason: MoneyChangeReason::Purchase(merch.id()),
+            });
+            info!(
+                "Tile spawn event on tile {:?} for tile type {:?}",
+                *on_tile, tile_id
+            );
+            tile_spawns.send(TileSpawnEvent {
+                tile_id,
+                on_tile: *on_tile,
+                owner: *buyer,
+                game,
+            });
         }
-        if errors.is_empty() {
-            Ok(())
-        } else {
-            Err(errors)
+    }
+
+    // Purchased tiles carry their `Merch` so later systems know what was paid for them
+    #[allow(clippy::type_complexity)]
+    fn tag_merchandise<T>(
+        mut commands: Commands,
+        registry: Res<MerchRegistry>,
+        tiles: Query<Entity, (Added<T>, With<Owner>, Without<Merch>)>,
+    ) where
+        T: Component + GetTypeRegistration,
+    {
+        let Some(merch) = registry.get::<T>() else {
+            return;
+        };
+        for tile in &tiles {
+            commands.entity(tile).insert(merch.clone());
         }
     }
 }
@@ -121,6 +271,7 @@ pub struct Purchase {
     buyer: Entity,
     merch: Merch,
     on_tile: Entity,
+    redo: bool,
 }
 
 impl Purchase {
@@ -129,24 +280,114 @@ impl Purchase {
             buyer,
             merch,
             on_tile,
+            redo: false,
         }
     }
+
+    pub(crate) fn redo(buyer: Entity, merch: Merch, on_tile: Entity) -> Self {
+        Purchase {
+            redo: true,
+            ..Self::new(buyer, merch, on_tile)
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Reflect)]
+pub enum MoneyChangeReason {
+    Purchase(MerchId),
+    Upgrade(MerchId),
+    Reorient,
+    Deposit,
+    Income,
+    Interest,
+    Refund,
+}
+
+impl std::fmt::Display for MoneyChangeReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Purchase(_) => write!(f, "purchase"),
+            Self::Upgrade(_) => write!(f, "upgrade"),
+            Self::Reorient => write!(f, "reorientation"),
+            Self::Deposit => write!(f, "resource deposit"),
+            Self::Income => write!(f, "income"),
+            Self::Interest => write!(f, "interest"),
+            Self::Refund => write!(f, "refund"),
+        }
+    }
+}
+
+// Sent whenever a player's `Money` changes so that the UI can keep a ledger
+#[derive(Clone, Copy, Debug)]
+#[derive(Event)]
+pub struct MoneyChanged {
+    pub player: Entity,
+    pub delta: isize,
+    pub reason: MoneyChangeReason,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq)]
 #[derive(Error)]
 #[derive(Reflect)]
 pub enum PurchaseError {
-    #[error("N")]
+    #[error("Cannot afford {item}: it costs {cost} but you only have {money}")]
     NotEnoughMoney {
-        shopper: Entity,
+        item: String,
         cost: Money,
         money: Money,
     },
-    #[error("N")]
-    UncontrolledTile { tile: Entity },
-    #[error("N")]
-    UnknownMerch { merch_id: MerchId },
+    #[error("Tile ({}, {}) is outside of your territory", .coordinates.x, .coordinates.y)]
+    UncontrolledTile { tile: Entity, coordinates: IVec2 },
+    #[error("Tile ({}, {}) is already occupied", .coordinates.x, .coordinates.y)]
+    Occupied { tile: Entity, coordinates: IVec2 },
+    #[error("{name} is not sold in this shop")]
+    UnknownMerch { merch_id: MerchId, name: String },
+    #[error("{name} cannot be bought before turn {unlock_turn}")]
+    Locked {
+        merch_id: MerchId,
+        name: String,
+        unlock_turn: usize,
+    },
+    #[error("{name} is sold out")]
+    SoldOut { merch_id: MerchId, name: String },
+    #[error("You cannot buy more than {cap} of {name}")]
+    PurchaseCapReached {
+        merch_id: MerchId,
+        name: String,
+        cap: usize,
+    },
+    #[error("There is no tower on tile ({}, {})", .coordinates.x, .coordinates.y)]
+    NotForSale { tile: Entity, coordinates: IVec2 },
+    #[error("You do not own the {name} on tile ({}, {})", .coordinates.x, .coordinates.y)]
+    NotOwned {
+        tile: Entity,
+        coordinates: IVec2,
+        name: String,
+    },
+    #[error("Towers can only be {action} while choosing")]
+    WrongPhase { tile: Entity, action: String },
+    #[error("{name} cannot be upgraded from level {current} to level {requested}")]
+    InvalidUpgrade {
+        tile: Entity,
+        name: String,
+        current: Level,
+        requested: Level,
+    },
+}
+
+// Sent for every purchase, sale, upgrade or reorientation that could not be carried out
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct PurchaseFailed {
+    pub player: Entity,
+    pub error: PurchaseError,
+}
+
+impl PurchaseFailed {
+    pub fn new(player: Entity, error: PurchaseError) -> Self {
+        PurchaseFailed { player, error }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -169,7 +410,10 @@ impl MerchAppExt for App {
         let mut registry = self.world_mut().resource_mut::<MerchRegistry>();
         let merch = match registry.register::<T>() {
             Ok(merch) => merch,
-            Err(merch) => merch,
+            Err(error) => {
+                error!("Failed to define merchandise: {}", error);
+                return;
+            }
         };
         let asset_server = self.world().resource::<AssetServer>();
         let material = T::material(asset_server);
@@ -177,5 +421,9 @@ impl MerchAppExt for App {
         let handle = materials.add(material);
         let mut textures = self.world_mut().resource_mut::<MerchMaterials>();
         textures.insert(merch.id(), handle);
+        self.add_systems(
+            Update,
+            MerchPlugin::tag_merchandise::<T>.in_set(MerchSystems),
+        );
     }
 }
@@ -1,41 +1,80 @@
 use std::any::TypeId;
 
+use thiserror::Error;
+
 use bevy::{
     prelude::*,
     reflect::GetTypeRegistration,
-    utils::{HashMap, TypeIdMap},
+    utils::{HashMap, HashSet, TypeIdMap},
 };
 
-use crate::{Merch, MerchId, Merchandise};
+use crate::{Merch, MerchCatalog, MerchId, MerchStock, Merchandise, Money};
 
 #[derive(Debug, Default)]
 #[derive(Resource)]
 pub struct MerchRegistry {
     by_type: TypeIdMap<Merch>,
     by_id: HashMap<MerchId, (TypeId, Merch)>,
-    next_id: MerchId,
+    limits: HashMap<MerchId, MerchLimits>,
+    upgradable: HashSet<MerchId>,
+}
+
+#[derive(Debug)]
+#[derive(Error)]
+pub enum MerchRegistrationError {
+    #[error("{type_path} was already registered as merchandise")]
+    AlreadyRegistered { type_path: &'static str },
+    #[error("Merch key {key} of {type_path} collides with the id of {existing}")]
+    KeyCollision {
+        key: &'static str,
+        type_path: &'static str,
+        existing: &'static str,
+    },
+}
+
+// Availability rules for a merch, everything is unlimited unless a catalog says otherwise
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[derive(Reflect)]
+pub struct MerchLimits {
+    // Copies shared between all players of a game
+    pub stock: Option<usize>,
+    // Copies a single player may buy over the whole game
+    pub player_cap: Option<usize>,
+    // First turn on which the merch can be bought
+    pub unlock_turn: usize,
 }
 
 impl MerchRegistry {
-    pub fn register<T>(&mut self) -> Result<Merch, Merch>
+    pub fn register<T>(&mut self) -> Result<Merch, MerchRegistrationError>
     where
         T: Merchandise + GetTypeRegistration,
     {
-        let merch_id = self.next_id;
-        let type_id = T::get_type_registration().type_id();
+        let registration = T::get_type_registration();
+        let type_id = registration.type_id();
+        let type_path = registration.type_info().type_path();
+        if self.by_type.contains_key(&type_id) {
+            return Err(MerchRegistrationError::AlreadyRegistered { type_path });
+        }
+
         let merch = Merch::new(
-            merch_id,
+            <T as Merchandise>::KEY,
             <T as Merchandise>::NAME,
             <T as Merchandise>::PRICE,
         );
-        let replaced_merch = self.by_type.insert(type_id, merch.clone());
-        let replaced_type = self.by_id.insert(merch_id, (type_id, merch.clone()));
-        *self.next_id += 1;
-        if let Some(merch) = replaced_merch.or(replaced_type.map(|(_, merch)| merch)) {
-            Err(merch)
-        } else {
-            Ok(merch)
+        if let Some((_, existing)) = self.by_id.get(&merch.id()) {
+            return Err(MerchRegistrationError::KeyCollision {
+                key: merch.key(),
+                type_path,
+                existing: existing.key(),
+            });
         }
+
+        self.by_type.insert(type_id, merch.clone());
+        self.by_id.insert(merch.id(), (type_id, merch.clone()));
+        if <T as Merchandise>::UPGRADABLE {
+            self.upgradable.insert(merch.id());
+        }
+        Ok(merch)
     }
 
     pub fn get<T: GetTypeRegistration>(&self) -> Option<&Merch> {
@@ -46,13 +85,82 @@ impl MerchRegistry {
         self.by_id.get(merch).map(|(id, _)| id)
     }
 
+    pub fn get_by_key(&self, key: &str) -> Option<&Merch> {
+        self.by_id
+            .get(&MerchId::from_key(key))
+            .map(|(_, merch)| merch)
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&TypeId, &Merch)> {
         self.by_type.iter()
     }
 
+    // Sorted by key, so the order does not depend on plugin registration order
     pub fn sorted(&self) -> Vec<(&TypeId, &Merch)> {
         let mut sorted = self.by_type.iter().collect::<Vec<_>>();
-        sorted.sort_by(|(_, merch1), (_, merch2)| merch1.id().cmp(&merch2.id()));
+        sorted.sort_by(|(_, merch1), (_, merch2)| merch1.key().cmp(merch2.key()));
         sorted
     }
+
+    // The price currently charged for a merch, which may differ from its `Merchandise::PRICE`
+    pub fn price(&self, merch: &MerchId) -> Option<Money> {
+        self.by_id.get(merch).map(|(_, merch)| merch.price())
+    }
+
+    pub fn set_price(&mut self, merch: &MerchId, price: Money) {
+        self.update(merch, |merch| merch.set_price(price));
+    }
+
+    pub fn is_upgradable(&self, merch: &MerchId) -> bool {
+        self.upgradable.contains(merch)
+    }
+
+    pub fn limits(&self, merch: &MerchId) -> MerchLimits {
+        self.limits.get(merch).copied().unwrap_or_default()
+    }
+
+    pub fn set_limits(&mut self, merch: &MerchId, limits: MerchLimits) {
+        self.limits.insert(*merch, limits);
+    }
+
+    // Whether the merch is unlocked on this turn and still in stock in the game
+    pub fn is_available(&self, merch: &Merch, turn: usize, stock: &MerchStock) -> bool {
+        let limits = self.limits(&merch.id());
+        turn >= limits.unlock_turn && stock.remaining(&merch.id(), &limits) != Some(0)
+    }
+
+    pub fn apply_catalog(&mut self, catalog: &MerchCatalog) {
+        for (key, entry) in catalog.merch.iter() {
+            let Some(merch_id) = self.get_by_key(key).map(Merch::id) else {
+                warn!("Catalog entry {} does not match any registered merch", key);
+                continue;
+            };
+            self.update(&merch_id, |merch| {
+                if let Some(name) = &entry.name {
+                    merch.set_name(name.clone());
+                }
+                if let Some(price) = entry.price {
+                    merch.set_price(Money::new(price));
+                }
+            });
+            self.set_limits(
+                &merch_id,
+                MerchLimits {
+                    stock: entry.stock,
+                    player_cap: entry.player_cap,
+                    unlock_turn: entry.unlock_turn,
+                },
+            );
+        }
+    }
+
+    fn update(&mut self, merch: &MerchId, mut update: impl FnMut(&mut Merch)) {
+        let Some((type_id, merch)) = self.by_id.get_mut(merch) else {
+            return;
+        };
+        update(merch);
+        if let Some(by_type) = self.by_type.get_mut(type_id) {
+            update(by_type);
+        }
+    }
 }
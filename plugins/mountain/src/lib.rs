@@ -1,8 +1,6 @@
 use bevy::{color::palettes, ecs::world::Command, prelude::*};
 
 use game_loop::InGame;
-use health::Health;
-use popups::PopupEvent;
 use tiles::{
     lasers::{Consumption, Direction, Position},
     Tile, TileParameters, TilePlugin,
@@ -20,6 +18,10 @@ impl Plugin for MountainPlugin {
 pub struct MountainTile;
 
 impl Tile for MountainTile {
+    const MAX_HEALTH: Option<usize> = Some(5);
+    const BLOCKS_TERRITORY: bool = true;
+    const TEXTURE: Option<&'static str> = Some("tiles/mountain.png");
+
     fn spawn(position: Position, _player: Entity, game: Entity) -> impl Command {
         MountainSpawn { position, game }
     }
@@ -37,14 +39,12 @@ impl Tile for MountainTile {
         MountainActivate {
             tile: entity,
             position: parameters.position,
+            vulnerable: Self::vulnerable(&parameters),
         }
     }
 
-    fn on_hit(&self, entity: Entity, strength: usize, _shooter: Entity) -> Option<impl Command> {
-        Some(MountainOnHit {
-            tile: entity,
-            strength,
-        })
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
     }
 }
 
@@ -55,46 +55,22 @@ pub struct MountainSpawn {
 
 impl Command for MountainSpawn {
     fn apply(self, world: &mut World) {
-        world.spawn((
-            MountainTile,
-            self.position,
-            Health::new(5),
-            InGame::new(self.game),
-        ));
+        world.spawn((MountainTile, self.position, InGame::new(self.game)));
     }
 }
 
 pub struct MountainActivate {
     tile: Entity,
     position: Position,
+    vulnerable: Vec<Direction>,
 }
 
 impl Command for MountainActivate {
     fn apply(self, world: &mut World) {
         world.spawn(Consumption::bundle(
             self.tile,
-            Direction::ALL.to_vec(),
+            self.vulnerable,
             self.position,
         ));
     }
 }
-
-pub struct MountainOnHit {
-    tile: Entity,
-    strength: usize,
-}
-
-impl Command for MountainOnHit {
-    fn apply(self, world: &mut World) {
-        let mut consumer_health = world
-            .get_mut::<Health>(self.tile)
-            .expect("MountainOnHit command should be fired for entity with a health component");
-        **consumer_health -= self.strength;
-        world.trigger_targets(
-            PopupEvent {
-                text: String::from(format!("-{}", self.strength)),
-            },
-            self.tile,
-        );
-    }
-}
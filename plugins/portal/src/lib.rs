@@ -0,0 +1,237 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    color::palettes,
+    ecs::{system::SystemState, world::Command},
+    prelude::*,
+    sprite::MaterialMesh2dBundle,
+};
+
+use game_loop::InGame;
+use merchandise::{MerchAppExt, Merchandise, Money};
+use shop::JustPurchased;
+use tilemap::TilemapLayout;
+use tiles::{
+    lasers::{Consumption, Direction, Position, Teleportation},
+    tile_material, Owner, Tile, TileParameters, TilePlugin,
+};
+
+pub struct PortalPlugin;
+
+impl Plugin for PortalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(TilePlugin::<PortalTile>::default())
+            .add_systems(Update, (Self::update_marker, Self::unlink_portals));
+        app.define_merchandise::<PortalTile>();
+    }
+}
+
+impl PortalPlugin {
+    fn update_marker(
+        tiles: Query<&Direction, (With<PortalTile>, Changed<Direction>)>,
+        mut markers: Query<(&Parent, &mut Transform), With<PortalMarker>>,
+    ) {
+        for (parent, mut transform) in &mut markers {
+            if let Ok(direction) = tiles.get(**parent) {
+                *transform = match *direction {
+                    Direction::North => Transform::IDENTITY,
+                    Direction::Northwest => {
+                        Transform::from_rotation(Quat::from_rotation_z(PI / 3.))
+                    }
+                    Direction::Southwest => {
+                        Transform::from_rotation(Quat::from_rotation_z(2. * PI / 3.))
+                    }
+                    Direction::South => Transform::from_rotation(Quat::from_rotation_z(PI)),
+                    Direction::Southeast => {
+                        Transform::from_rotation(Quat::from_rotation_z(4. * PI / 3.))
+                    }
+                    Direction::Northeast => {
+                        Transform::from_rotation(Quat::from_rotation_z(5. * PI / 3.))
+                    }
+                };
+            }
+        }
+    }
+
+    // A portal whose partner was destroyed, sold or undone waits for the next purchase to relink
+    fn unlink_portals(
+        mut commands: Commands,
+        links: Query<(Entity, &PortalLink)>,
+        portals: Query<(), With<PortalTile>>,
+    ) {
+        for (entity, link) in &links {
+            if portals.get(**link).is_err() {
+                info!("Portal {:?} lost its partner", entity);
+                commands.entity(entity).remove::<PortalLink>();
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct PortalTile;
+
+impl Tile for PortalTile {
+    const MAX_HEALTH: Option<usize> = Some(2);
+    const OWNER_IMMUNE: bool = true;
+    const TEXTURE: Option<&'static str> = Some("tiles/portal.png");
+
+    fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
+        PortalSpawn { position, player }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        Color::Srgba(palettes::css::DARK_VIOLET).into()
+    }
+
+    fn activate(
+        &self,
+        entity: Entity,
+        parameters: TileParameters,
+        _shooter: Option<Entity>,
+    ) -> impl Command {
+        PortalActivate {
+            tile: entity,
+            position: parameters.position,
+            vulnerable: Self::vulnerable(&parameters),
+        }
+    }
+
+    // Every laser from another player sent through the portal wears it down
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
+}
+
+impl Merchandise for PortalTile {
+    const KEY: &'static str = "portal";
+    const PRICE: Money = Money::new(6);
+    const NAME: &'static str = "Portal";
+
+    fn material(asset_server: &AssetServer) -> ColorMaterial {
+        let mut base = tile_material::<Self>(asset_server, 0.);
+        base.color.set_alpha(0.6);
+        base
+    }
+}
+
+// The partner portal that lasers entering this one leave from
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Deref, Reflect)]
+pub struct PortalLink(Entity);
+
+pub struct PortalSpawn {
+    position: Position,
+    player: Entity,
+}
+
+impl Command for PortalSpawn {
+    #[allow(clippy::type_complexity)]
+    fn apply(self, world: &mut World) {
+        let mut system_state: SystemState<(
+            Query<&TilemapLayout>,
+            Query<(Entity, &Owner), (With<PortalTile>, Without<PortalLink>)>,
+            ResMut<Assets<Mesh>>,
+            ResMut<Assets<ColorMaterial>>,
+        )> = SystemState::new(world);
+
+        let (layout, unlinked, mut meshes, mut materials) = system_state.get_mut(world);
+
+        let Ok(translation) = layout
+            .get_single()
+            .map(|layout| layout.hex_to_world_pos(*self.position).extend(11.))
+        else {
+            info!("Did not get the single tilemap layout for the game");
+            return;
+        };
+
+        // Portals are bought in pairs, each purchase links to the player's unlinked portal
+        let partner = unlinked
+            .iter()
+            .find(|(_, owner)| ***owner == self.player)
+            .map(|(entity, _)| entity);
+
+        let ring = meshes.add(Annulus::new(14., 20.));
+        let black = materials.add(Color::BLACK);
+        let triangle = meshes.add(Triangle2d::new(
+            Vec2::new(-5., 22.),
+            Vec2::new(5., 22.),
+            Vec2::new(0., 32.),
+        ));
+        let white = materials.add(Color::WHITE);
+
+        let Some(game) = world.get::<InGame>(self.player).cloned() else {
+            return;
+        };
+        let portal = world
+            .spawn((
+                PortalTile,
+                self.position,
+                Direction::default(),
+                Owner::new(self.player),
+                game,
+                Transform::from_translation(translation),
+                GlobalTransform::from_translation(translation),
+                JustPurchased,
+            ))
+            .with_children(|builder| {
+                builder.spawn(MaterialMesh2dBundle {
+                    mesh: ring.into(),
+                    material: black,
+                    transform: Transform::default(),
+                    ..default()
+                });
+                builder.spawn((
+                    PortalMarker,
+                    MaterialMesh2dBundle {
+                        mesh: triangle.into(),
+                        material: white,
+                        transform: Transform::default(),
+                        ..default()
+                    },
+                ));
+            })
+            .id();
+
+        if let Some(partner) = partner {
+            info!("Linking portal {:?} with {:?}", portal, partner);
+            world.entity_mut(portal).insert(PortalLink(partner));
+            world.entity_mut(partner).insert(PortalLink(portal));
+        }
+    }
+}
+
+pub struct PortalActivate {
+    tile: Entity,
+    position: Position,
+    vulnerable: Vec<Direction>,
+}
+
+impl Command for PortalActivate {
+    fn apply(self, world: &mut World) {
+        // Lasers leave the partner portal in the direction it faces
+        let teleportation = world
+            .get::<PortalLink>(self.tile)
+            .and_then(|link| {
+                Some((
+                    *world.get::<Position>(**link)?,
+                    *world.get::<Direction>(**link)?,
+                ))
+            })
+            .map(|(exit, direction)| Teleportation::new(exit, direction));
+
+        let mut consumption = world.spawn(Consumption::passing_bundle(
+            self.tile,
+            self.vulnerable,
+            self.position,
+        ));
+        if let Some(teleportation) = teleportation {
+            consumption.insert(teleportation);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct PortalMarker;
@@ -12,8 +12,8 @@ use merchandise::{MerchAppExt, Merchandise, Money};
 use shop::JustPurchased;
 use tilemap::TilemapLayout;
 use tiles::{
-    lasers::{Direction, Position, Reflection},
-    Owner, Tile, TileParameters, TilePlugin,
+    lasers::{Consumption, Direction, Position, Reflection},
+    tile_material, Owner, Tile, TileParameters, TilePlugin,
 };
 
 pub struct ReflectorPlugin;
@@ -52,6 +52,10 @@ impl ReflectorPlugin {
 pub struct ReflectorTile;
 
 impl Tile for ReflectorTile {
+    const MAX_HEALTH: Option<usize> = Some(2);
+    const OWNER_IMMUNE: bool = true;
+    const TEXTURE: Option<&'static str> = Some("tiles/reflector.png");
+
     fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
         ReflectorSpawn { position, player }
     }
@@ -62,25 +66,33 @@ impl Tile for ReflectorTile {
 
     fn activate(
         &self,
-        _entity: Entity,
+        entity: Entity,
         parameters: TileParameters,
         _shooter: Option<Entity>,
     ) -> impl Command {
         ReflectorActivate {
+            tile: entity,
             position: parameters.position,
             direction: parameters
                 .direction
                 .unwrap_or_else(|| panic!("Reflector needs a direction")),
+            vulnerable: Self::vulnerable(&parameters),
         }
     }
+
+    // Every laser from another player reflected by the tile wears it down
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
 }
 
 impl Merchandise for ReflectorTile {
+    const KEY: &'static str = "reflector";
     const PRICE: Money = Money::new(5);
     const NAME: &'static str = "Reflector Tower";
 
     fn material(asset_server: &AssetServer) -> ColorMaterial {
-        let mut base = <Self as Tile>::material(asset_server);
+        let mut base = tile_material::<Self>(asset_server, 0.);
         base.color.set_alpha(0.6);
         base
     }
@@ -140,13 +152,18 @@ impl Command for ReflectorSpawn {
 }
 
 pub struct ReflectorActivate {
+    tile: Entity,
     position: Position,
     direction: Direction,
+    vulnerable: Vec<Direction>,
 }
 
 impl Command for ReflectorActivate {
     fn apply(self, world: &mut World) {
-        world.spawn((Reflection::new(self.direction), self.position));
+        world.spawn((
+            Reflection::new(self.direction),
+            Consumption::passing_bundle(self.tile, self.vulnerable, self.position),
+        ));
     }
 }
 
@@ -4,18 +4,16 @@ use bevy::{
     color::palettes,
     ecs::{system::SystemState, world::Command},
     prelude::*,
-    sprite::MaterialMesh2dBundle,
+    sprite::Anchor,
 };
 
 use game_loop::InGame;
-use health::Health;
 use merchandise::{MerchAppExt, Merchandise, Money};
-use popups::PopupEvent;
 use shop::JustPurchased;
 use tilemap::TilemapLayout;
 use tiles::{
-    lasers::{Consumption, Direction, Position, Refraction},
-    Owner, Tile, TileParameters, TilePlugin,
+    lasers::{Armor, ArmorKind, Consumption, Direction, Position, Refraction},
+    marker_sprite, tile_material, Owner, Tile, TileParameters, TilePlugin,
 };
 
 pub struct RefractorPlugin;
@@ -62,6 +60,9 @@ impl RefractorPlugin {
 pub struct RefractorTile;
 
 impl Tile for RefractorTile {
+    const MAX_HEALTH: Option<usize> = Some(3);
+    const TEXTURE: Option<&'static str> = Some("tiles/refractor.png");
+
     fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
         RefractorSpawn { position, player }
     }
@@ -82,23 +83,38 @@ impl Tile for RefractorTile {
             direction: parameters
                 .direction
                 .unwrap_or_else(|| panic!("Refractor needs a direction")),
+            vulnerable: Self::vulnerable(&parameters),
+            armor: Self::armor(&parameters),
         }
     }
 
-    fn on_hit(&self, entity: Entity, strength: usize, _shooter: Entity) -> Option<impl Command> {
-        Some(RefractorOnHit {
-            tile: entity,
-            strength,
-        })
+    fn vulnerable(parameters: &TileParameters) -> Vec<Direction> {
+        parameters
+            .direction
+            .map(|direction| direction.front_directions().to_vec())
+            .unwrap_or_default()
+    }
+
+    // Lasers glance off the back corners, leaving only the face directly behind exposed
+    fn armor(parameters: &TileParameters) -> Armor {
+        parameters
+            .direction
+            .map(|direction| {
+                Armor::default()
+                    .face(direction.opposite().clockwise(1), ArmorKind::Deflect)
+                    .face(direction.opposite().counterclockwise(1), ArmorKind::Deflect)
+            })
+            .unwrap_or_default()
     }
 }
 
 impl Merchandise for RefractorTile {
+    const KEY: &'static str = "refractor";
     const PRICE: Money = Money::new(5);
     const NAME: &'static str = "Refractor Tower";
 
     fn material(asset_server: &AssetServer) -> ColorMaterial {
-        let mut base = <Self as Tile>::material(asset_server);
+        let mut base = tile_material::<Self>(asset_server, 0.);
         base.color.set_alpha(0.6);
         base
     }
@@ -111,13 +127,10 @@ pub struct RefractorSpawn {
 
 impl Command for RefractorSpawn {
     fn apply(self, world: &mut World) {
-        let mut system_state: SystemState<(
-            Query<&TilemapLayout>,
-            ResMut<Assets<Mesh>>,
-            ResMut<Assets<ColorMaterial>>,
-        )> = SystemState::new(world);
+        let mut system_state: SystemState<(Query<&TilemapLayout>, Res<AssetServer>)> =
+            SystemState::new(world);
 
-        let (layout, mut meshes, mut materials) = system_state.get_mut(world);
+        let (layout, asset_server) = system_state.get(world);
 
         let Ok(translation) = layout
             .get_single()
@@ -127,14 +140,23 @@ impl Command for RefractorSpawn {
             return;
         };
 
-        let rectangle = meshes.add(Rectangle::new(60., 5.));
-        let black = materials.add(Color::BLACK);
-        let triangle = meshes.add(Triangle2d::new(
-            Vec2::new(-5., -8.),
-            Vec2::new(5., -8.),
-            Vec2::new(0., -18.),
-        ));
-        let red = materials.add(Color::Srgba(bevy::color::palettes::css::RED));
+        let prism = marker_sprite(
+            &asset_server,
+            "markers/refractor.png",
+            Color::WHITE,
+            Color::BLACK,
+            Vec2::new(60., 5.),
+            Anchor::Center,
+        );
+        // The arrow hangs below the prism, pointing away from its refracting side
+        let arrow = marker_sprite(
+            &asset_server,
+            "markers/refractor_arrow.png",
+            Color::WHITE,
+            Color::Srgba(palettes::css::RED),
+            Vec2::new(10., 10.),
+            Anchor::Custom(Vec2::new(0., 1.3)),
+        );
 
         if let Some(game) = world.get::<InGame>(self.player) {
             world
@@ -150,25 +172,8 @@ impl Command for RefractorSpawn {
                 ))
                 .with_children(|builder| {
                     info!("Spawning child marker for refractor");
-                    builder.spawn((
-                        RefractorMarker,
-                        MaterialMesh2dBundle {
-                            mesh: rectangle.into(),
-                            material: black,
-                            transform: Transform::default(),
-                            ..default()
-                        },
-                    ));
-
-                    builder.spawn((
-                        RefractorMarker,
-                        MaterialMesh2dBundle {
-                            mesh: triangle.into(),
-                            material: red,
-                            transform: Transform::default(),
-                            ..default()
-                        },
-                    ));
+                    builder.spawn((RefractorMarker, prism));
+                    builder.spawn((RefractorMarker, arrow));
                 });
         }
     }
@@ -178,41 +183,20 @@ pub struct RefractorActivate {
     tile: Entity,
     position: Position,
     direction: Direction,
+    vulnerable: Vec<Direction>,
+    armor: Armor,
 }
 
 impl Command for RefractorActivate {
     fn apply(self, world: &mut World) {
         world.spawn((
             Refraction::new(self.direction),
-            Consumption::bundle(
-                self.tile,
-                self.direction.front_directions().to_vec(),
-                self.position.clone(),
-            ),
+            Consumption::bundle(self.tile, self.vulnerable, self.position.clone()),
+            self.armor,
         ));
     }
 }
 
-pub struct RefractorOnHit {
-    tile: Entity,
-    strength: usize,
-}
-
-impl Command for RefractorOnHit {
-    fn apply(self, world: &mut World) {
-        let mut consumer_health = world
-            .get_mut::<Health>(self.tile)
-            .expect("RefractorOnHit entity should have Health component added to it");
-        **consumer_health -= self.strength;
-        world.trigger_targets(
-            PopupEvent {
-                text: String::from(format!("-{}", self.strength)),
-            },
-            self.tile,
-        );
-    }
-}
-
 #[derive(Clone, Copy, Debug)]
 #[derive(Component)]
 pub struct RefractorMarker;
@@ -2,20 +2,116 @@ use std::cmp::min;
 
 use bevy::{color::palettes, ecs::world::Command, prelude::*};
 
-use game_loop::InGame;
+use game_loop::{GamePhase, InGame};
 use health::Health;
-use merchandise::Money;
-use popups::PopupEvent;
+use merchandise::{Money, MoneyChangeReason, MoneyChanged, Shopper};
 use tiles::{
-    lasers::{Consumption, Direction, Position},
-    Tile, TileParameters, TilePlugin,
+    lasers::{Consumption, Direction, LaserKind, Position},
+    DamageTile, Influence, Owner, Tile, TileParameters, TilePlugin, TileSystems,
 };
 
 pub struct ResourceDepositPlugin;
 
 impl Plugin for ResourceDepositPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(TilePlugin::<ResourceDepositTile>::default());
+        app.register_type::<DepositRules>()
+            .add_plugins(TilePlugin::<ResourceDepositTile>::default())
+            .add_systems(
+                Update,
+                (
+                    Self::split_extractions.after(TileSystems::OnHit),
+                    Self::regenerate_deposits,
+                ),
+            );
+    }
+}
+
+impl ResourceDepositPlugin {
+    // Everything extracted from a deposit during an act phase is paid out at once, so players
+    // hitting the same deposit share it instead of the first laser draining it
+    #[allow(clippy::type_complexity)]
+    fn split_extractions(
+        mut commands: Commands,
+        mut deposits: Query<
+            (Entity, &mut Money, &Extractions, Option<&Owner>),
+            With<ResourceDepositTile>,
+        >,
+        mut shoppers: Query<&mut Money, (With<Shopper>, Without<ResourceDepositTile>)>,
+        mut money_changes: EventWriter<MoneyChanged>,
+    ) {
+        for (deposit, mut money, extractions, owner) in &mut deposits {
+            let shares = extractions.split(**money);
+
+            for (shooter, share) in shares.iter().filter(|(_, share)| *share > 0) {
+                let Ok(mut shooter_money) = shoppers.get_mut(*shooter) else {
+                    continue;
+                };
+                **money -= share;
+                **shooter_money += share;
+                money_changes.send(MoneyChanged {
+                    player: *shooter,
+                    delta: *share as isize,
+                    reason: MoneyChangeReason::Deposit,
+                });
+            }
+
+            // The player taking the largest share claims the deposit, ties leave it as it was
+            let largest = shares.iter().map(|(_, share)| *share).max().unwrap_or(0);
+            let mut leaders = shares
+                .iter()
+                .filter(|(_, share)| largest > 0 && *share == largest);
+            if let (Some((leader, _)), None) = (leaders.next(), leaders.next()) {
+                if owner.map(|owner| **owner) != Some(*leader) {
+                    info!("Deposit {} claimed by player {}", deposit, leader);
+                    commands.entity(deposit).insert(Owner::new(*leader));
+                }
+            }
+
+            commands.entity(deposit).remove::<Extractions>();
+        }
+    }
+
+    // Deposits refill a little at the start of every turn and then pay their owner
+    #[allow(clippy::type_complexity)]
+    fn regenerate_deposits(
+        games: Query<(Entity, Ref<GamePhase>, Option<&DepositRules>)>,
+        mut deposits: Query<(&mut Money, &InGame, Option<&Owner>), With<ResourceDepositTile>>,
+        mut shoppers: Query<&mut Money, (With<Shopper>, Without<ResourceDepositTile>)>,
+        mut money_changes: EventWriter<MoneyChanged>,
+    ) {
+        for (game, phase, rules) in &games {
+            if !phase.is_changed() || phase.is_added() || !matches!(*phase, GamePhase::Choose) {
+                continue;
+            }
+            let rules = rules.copied().unwrap_or_default();
+
+            for (mut money, _, owner) in deposits
+                .iter_mut()
+                .filter(|(_, in_game, _)| ***in_game == game)
+            {
+                // Deposits refilled above their capacity by other means are left alone
+                if **money < rules.money {
+                    **money = min(**money + rules.regeneration, rules.money);
+                }
+
+                let Some(owner) = owner else {
+                    continue;
+                };
+                let Ok(mut owner_money) = shoppers.get_mut(**owner) else {
+                    continue;
+                };
+                let payout = min(**money, rules.passive_income);
+                if payout > 0 {
+                    **money -= payout;
+                    **owner_money += payout;
+                    money_changes.send(MoneyChanged {
+                        player: **owner,
+                        delta: payout as isize,
+                        reason: MoneyChangeReason::Deposit,
+                    });
+                }
+            }
+        }
     }
 }
 
@@ -23,7 +119,16 @@ impl Plugin for ResourceDepositPlugin {
 #[derive(Component, Reflect)]
 pub struct ResourceDepositTile;
 
+impl ResourceDepositTile {
+    // Mining lasers extract this many times their strength in money
+    pub const MINING_YIELD: usize = 2;
+}
+
 impl Tile for ResourceDepositTile {
+    const TEXTURE: Option<&'static str> = Some("tiles/resource_deposit.png");
+    // Claimed deposits only extend their owner's territory a little
+    const INFLUENCE: Influence = Influence::new(2, 1);
+
     fn spawn(position: Position, _player: Entity, game: Entity) -> impl Command {
         ResourceDepositSpawn { position, game }
     }
@@ -41,14 +146,26 @@ impl Tile for ResourceDepositTile {
         ResourceDepositActivate {
             tile: entity,
             position: parameters.position,
+            vulnerable: Self::vulnerable(&parameters),
         }
     }
 
-    fn on_hit(&self, entity: Entity, strength: usize, shooter: Entity) -> Option<impl Command> {
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
+
+    fn on_hit(
+        &self,
+        entity: Entity,
+        strength: usize,
+        shooter: Entity,
+        kind: LaserKind,
+    ) -> Option<impl Command> {
         Some(ResourceDepositOnHit {
             tile: entity,
             strength,
             shooter,
+            kind,
         })
     }
 }
@@ -60,20 +177,32 @@ pub struct ResourceDepositSpawn {
 
 impl Command for ResourceDepositSpawn {
     fn apply(self, world: &mut World) {
-        world.spawn((ResourceDepositTile, self.position, InGame::new(self.game)));
+        let rules = world
+            .get::<DepositRules>(self.game)
+            .copied()
+            .unwrap_or_default();
+
+        world.spawn((
+            ResourceDepositTile,
+            self.position,
+            InGame::new(self.game),
+            Money::new(rules.money),
+            Health::new(rules.health),
+        ));
     }
 }
 
 pub struct ResourceDepositActivate {
     tile: Entity,
     position: Position,
+    vulnerable: Vec<Direction>,
 }
 
 impl Command for ResourceDepositActivate {
     fn apply(self, world: &mut World) {
         world.spawn(Consumption::bundle(
             self.tile,
-            Direction::ALL.to_vec(),
+            self.vulnerable,
             self.position.clone(),
         ));
     }
@@ -83,29 +212,144 @@ pub struct ResourceDepositOnHit {
     tile: Entity,
     strength: usize,
     shooter: Entity,
+    kind: LaserKind,
 }
 
 impl Command for ResourceDepositOnHit {
     fn apply(self, world: &mut World) {
-        let mut query = world.query::<(&mut Money, &mut Health)>();
-        let [(mut resource_money, mut resource_health), (mut shooter_money, _)] =
-            query.get_many_mut(world, [self.tile, self.shooter]).expect(
-                "Shooting player and resource tile should both have health and money components",
-            );
+        let Some(money) = world.get::<Money>(self.tile).copied() else {
+            info!("Resource deposit {} has no money to extract", self.tile);
+            return;
+        };
+
+        // Drained deposits are worn down by lasers until they are refilled
+        if *money == 0 {
+            DamageTile {
+                tile: self.tile,
+                strength: self.strength,
+            }
+            .apply(world);
+            return;
+        }
 
-        if **resource_money > 0 {
-            let money_transfer = min(**resource_money, self.strength);
-            **resource_money -= money_transfer;
-            **shooter_money += money_transfer;
+        let extracted = match self.kind {
+            LaserKind::Mining => self.strength * ResourceDepositTile::MINING_YIELD,
+            _ => self.strength,
+        };
+        let Some(mut deposit) = world.get_entity_mut(self.tile) else {
+            return;
+        };
+        if let Some(mut extractions) = deposit.get_mut::<Extractions>() {
+            extractions.add(self.shooter, extracted);
         } else {
-            let health_decrease = min(**resource_health, self.strength);
-            **resource_health -= health_decrease;
-            world.trigger_targets(
-                PopupEvent {
-                    text: String::from(format!("-{}", self.strength)),
-                },
-                self.tile,
-            );
+            deposit.insert(Extractions(vec![(self.shooter, extracted)]));
+        }
+    }
+}
+
+// Per-game deposit configuration, games without it use the default rules
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Component, Reflect)]
+pub struct DepositRules {
+    // Money a deposit spawns with, regeneration never refills it past this
+    pub money: usize,
+    pub health: usize,
+    // Money regained at the start of every turn
+    pub regeneration: usize,
+    // Money paid to the owner of a deposit at the start of every turn
+    pub passive_income: usize,
+}
+
+impl Default for DepositRules {
+    fn default() -> Self {
+        DepositRules {
+            money: 20,
+            health: 5,
+            regeneration: 1,
+            passive_income: 1,
+        }
+    }
+}
+
+// Money each player's lasers tried to extract from a deposit during the current act phase
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Deref, DerefMut)]
+pub struct Extractions(Vec<(Entity, usize)>);
+
+impl Extractions {
+    pub fn add(&mut self, shooter: Entity, amount: usize) {
+        if let Some((_, extracted)) = self.iter_mut().find(|(player, _)| *player == shooter) {
+            *extracted += amount;
+        } else {
+            self.push((shooter, amount));
+        }
+    }
+
+    // Hands out the available money one unit at a time in turn, so every player gets an equal
+    // share capped by how much their own lasers extracted
+    pub fn split(&self, available: usize) -> Vec<(Entity, usize)> {
+        let mut shares = self
+            .iter()
+            .map(|(player, _)| (*player, 0))
+            .collect::<Vec<_>>();
+        let mut remaining = available;
+
+        while remaining > 0 {
+            let mut paid = false;
+            for ((_, share), (_, extracted)) in shares.iter_mut().zip(self.iter()) {
+                if remaining > 0 && *share < *extracted {
+                    *share += 1;
+                    remaining -= 1;
+                    paid = true;
+                }
+            }
+            if !paid {
+                break;
+            }
         }
+
+        shares
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Entity;
+
+    use super::Extractions;
+
+    #[test]
+    fn test_single_player_split() {
+        let player = Entity::from_raw(1);
+        let mut extractions = Extractions::default();
+        extractions.add(player, 2);
+        extractions.add(player, 3);
+
+        assert_eq!(extractions.split(20), vec![(player, 5)]);
+        assert_eq!(extractions.split(3), vec![(player, 3)]);
+    }
+
+    #[test]
+    fn test_unequal_split_with_limited_money() {
+        let first = Entity::from_raw(1);
+        let second = Entity::from_raw(2);
+        let mut extractions = Extractions::default();
+        extractions.add(first, 6);
+        extractions.add(second, 2);
+
+        // Shared equally until the smaller extraction is paid out, the rest goes to the other
+        assert_eq!(extractions.split(5), vec![(first, 3), (second, 2)]);
+        assert_eq!(extractions.split(3), vec![(first, 2), (second, 1)]);
+    }
+
+    #[test]
+    fn test_split_with_more_money_than_extracted() {
+        let first = Entity::from_raw(1);
+        let second = Entity::from_raw(2);
+        let mut extractions = Extractions::default();
+        extractions.add(first, 4);
+        extractions.add(second, 1);
+
+        assert_eq!(extractions.split(100), vec![(first, 4), (second, 1)]);
     }
 }
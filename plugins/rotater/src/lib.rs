@@ -4,7 +4,7 @@ use bevy::{
     color::palettes,
     ecs::{system::SystemState, world::Command},
     prelude::*,
-    sprite::{MaterialMesh2dBundle, Mesh2dHandle},
+    sprite::Anchor,
 };
 
 use game_loop::InGame;
@@ -12,8 +12,8 @@ use merchandise::{MerchAppExt, Merchandise, Money};
 use shop::JustPurchased;
 use tilemap::TilemapLayout;
 use tiles::{
-    lasers::{Position, Rotation},
-    Owner, Tile, TileParameters, TilePlugin,
+    lasers::{Consumption, Direction, Position, Rotation},
+    marker_sprite, tile_material, Owner, Tile, TileParameters, TilePlugin,
 };
 
 pub struct RotaterPlugin;
@@ -27,42 +27,14 @@ impl Plugin for RotaterPlugin {
 }
 
 impl RotaterPlugin {
+    // The arrow points to where a laser coming in from the south leaves the tile
     fn update_marker(
         tiles: Query<&Rotation, (With<RotaterTile>, Changed<Rotation>)>,
-        mut markers: Query<(&Parent, &mut Transform, &mut Mesh2dHandle), With<RotaterMarker>>,
-        mut meshes: ResMut<Assets<Mesh>>,
+        mut markers: Query<(&Parent, &mut Transform), With<RotaterMarker>>,
     ) {
-        for (parent, mut transform, mut mesh) in &mut markers {
-            if let Ok(rotation) = tiles.get(**parent).and_then(|rotation| Ok(**rotation % 6)) {
-                (*transform, *mesh) = match rotation {
-                    0 => (
-                        Transform::from_rotation(Quat::from_rotation_z(-PI / 3.)),
-                        meshes.add(CircularSector::new(40., 0.)).into(),
-                    ),
-                    1 => (
-                        Transform::from_rotation(Quat::from_rotation_z(-PI / 3.)),
-                        meshes.add(CircularSector::new(40., PI / 6.)).into(),
-                    ),
-                    2 => (
-                        Transform::from_rotation(Quat::from_rotation_z(-PI / 6.)),
-                        meshes.add(CircularSector::new(40., PI / 3.)).into(),
-                    ),
-                    3 => (
-                        Transform::IDENTITY,
-                        meshes.add(CircularSector::new(40., PI / 2.)).into(),
-                    ),
-                    4 => (
-                        Transform::from_rotation(Quat::from_rotation_z(PI / 6.)),
-                        meshes.add(CircularSector::new(40., 2. * PI / 3.)).into(),
-                    ),
-                    5 => (
-                        Transform::from_rotation(Quat::from_rotation_z(PI / 3.)),
-                        meshes.add(CircularSector::new(40., 5. * PI / 6.)).into(),
-                    ),
-                    _ => unreachable!(
-                        "All modulus cases for possible rotations should be covered already"
-                    ),
-                };
+        for (parent, mut transform) in &mut markers {
+            if let Ok(rotation) = tiles.get(**parent) {
+                *transform = RotaterMarker::transform(rotation);
                 info!("Changed rotation marker to match parameters");
             }
         }
@@ -74,6 +46,10 @@ impl RotaterPlugin {
 pub struct RotaterTile;
 
 impl Tile for RotaterTile {
+    const MAX_HEALTH: Option<usize> = Some(2);
+    const OWNER_IMMUNE: bool = true;
+    const TEXTURE: Option<&'static str> = Some("tiles/rotater.png");
+
     fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
         RotaterSpawn { position, player }
     }
@@ -84,25 +60,33 @@ impl Tile for RotaterTile {
 
     fn activate(
         &self,
-        _entity: Entity,
+        entity: Entity,
         parameters: TileParameters,
         _shooter: Option<Entity>,
     ) -> impl Command {
         RotaterActivate {
+            tile: entity,
             position: parameters.position,
             rotation: parameters
                 .rotation
                 .unwrap_or_else(|| panic!("Rotator needs a rotation")),
+            vulnerable: Self::vulnerable(&parameters),
         }
     }
+
+    // Every laser from another player turned by the tile wears it down
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
 }
 
 impl Merchandise for RotaterTile {
+    const KEY: &'static str = "rotater";
     const PRICE: Money = Money::new(5);
     const NAME: &'static str = "Rotater Tower";
 
     fn material(asset_server: &AssetServer) -> ColorMaterial {
-        let mut base = <Self as Tile>::material(asset_server);
+        let mut base = tile_material::<Self>(asset_server, 0.);
         base.color.set_alpha(0.6);
         base
     }
@@ -115,13 +99,10 @@ pub struct RotaterSpawn {
 
 impl Command for RotaterSpawn {
     fn apply(self, world: &mut World) {
-        let mut system_state: SystemState<(
-            Query<&TilemapLayout>,
-            ResMut<Assets<Mesh>>,
-            ResMut<Assets<ColorMaterial>>,
-        )> = SystemState::new(world);
+        let mut system_state: SystemState<(Query<&TilemapLayout>, Res<AssetServer>)> =
+            SystemState::new(world);
 
-        let (layout, mut meshes, mut materials) = system_state.get_mut(world);
+        let (layout, asset_server) = system_state.get(world);
 
         let Ok(translation) = layout
             .get_single()
@@ -131,15 +112,22 @@ impl Command for RotaterSpawn {
             return;
         };
 
-        let sector = meshes.add(CircularSector::new(40., PI / 6.));
-        let white = materials.add(Color::WHITE);
+        let rotation = Rotation::new(1);
+        let marker = marker_sprite(
+            &asset_server,
+            "markers/rotater.png",
+            Color::WHITE,
+            Color::WHITE,
+            Vec2::new(10., 40.),
+            Anchor::BottomCenter,
+        );
 
         if let Some(game) = world.get::<InGame>(self.player) {
             world
                 .spawn((
                     RotaterTile,
                     self.position,
-                    Rotation::new(1),
+                    rotation,
                     Owner::new(self.player),
                     game.clone(),
                     Transform::from_translation(translation),
@@ -147,31 +135,37 @@ impl Command for RotaterSpawn {
                     JustPurchased,
                 ))
                 .with_children(|builder| {
-                    builder.spawn((
-                        RotaterMarker,
-                        MaterialMesh2dBundle {
-                            mesh: sector.into(),
-                            material: white,
-                            transform: Transform::from_rotation(Quat::from_rotation_z(-PI / 3.)),
-                            ..default()
-                        },
-                    ));
+                    builder
+                        .spawn((RotaterMarker, marker))
+                        .insert(RotaterMarker::transform(&rotation));
                 });
         }
     }
 }
 
 pub struct RotaterActivate {
+    tile: Entity,
     position: Position,
     rotation: Rotation,
+    vulnerable: Vec<Direction>,
 }
 
 impl Command for RotaterActivate {
     fn apply(self, world: &mut World) {
-        world.spawn((Rotation::new(self.rotation.get()), self.position));
+        world.spawn((
+            Rotation::new(self.rotation.get()),
+            Consumption::passing_bundle(self.tile, self.vulnerable, self.position),
+        ));
     }
 }
 
 #[derive(Clone, Copy, Debug)]
 #[derive(Component)]
 pub struct RotaterMarker;
+
+impl RotaterMarker {
+    // Rotations turn lasers counterclockwise by a sixth of a turn per step
+    fn transform(rotation: &Rotation) -> Transform {
+        Transform::from_rotation(Quat::from_rotation_z((rotation.get() % 6) as f32 * PI / 3.))
+    }
+}
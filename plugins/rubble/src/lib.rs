@@ -0,0 +1,180 @@
+use bevy::{color::palettes, ecs::world::Command, prelude::*};
+
+use game_loop::{GamePhase, InGame, Turn};
+use health::{Health, HealthSystems};
+use merchandise::Merch;
+use tilemap::{EmptyTile, TilemapEntities};
+use tiles::{
+    lasers::{Consumption, Direction, Position},
+    Tile, TileOccupancy, TileParameters, TilePlugin,
+};
+
+// Destroyed towers leave rubble behind that absorbs lasers until it clears a few turns later
+pub struct RubblePlugin;
+
+impl Plugin for RubblePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(TilePlugin::<RubbleTile>::default())
+            .add_systems(
+                Update,
+                (
+                    Self::spawn_rubble.before(HealthSystems),
+                    Self::free_map_features.before(HealthSystems),
+                    Self::clear_rubble,
+                ),
+            );
+    }
+}
+
+impl RubblePlugin {
+    // Only purchased towers turn into rubble, map features like mountains simply disappear
+    #[allow(clippy::type_complexity)]
+    fn spawn_rubble(
+        mut commands: Commands,
+        destroyed: Query<(&Position, &InGame, &Health), (Changed<Health>, With<Merch>)>,
+    ) {
+        for (position, game, _) in destroyed.iter().filter(|(_, _, health)| ***health == 0) {
+            info!("Tower destroyed, leaving rubble at {:?}", position);
+            commands.add(RubbleSpawn {
+                position: *position,
+                game: **game,
+            });
+        }
+    }
+
+    // Destroyed map features and rubble leave their hex empty right away, unless a tile is stacked on them
+    #[allow(clippy::type_complexity)]
+    fn free_map_features(
+        mut commands: Commands,
+        destroyed: Query<(Entity, &Position, &InGame, &Health), (Changed<Health>, Without<Merch>)>,
+        occupancies: Query<&TileOccupancy>,
+        tilemaps: Query<&TilemapEntities>,
+    ) {
+        for (entity, position, game, _) in destroyed.iter().filter(|(.., health)| ***health == 0) {
+            let shared = occupancies.get(**game).is_ok_and(|occupancy| {
+                occupancy
+                    .occupants(position)
+                    .any(|occupant| occupant != entity)
+            });
+            if shared {
+                continue;
+            }
+            if let Some(tile) = tilemaps
+                .iter()
+                .find_map(|tilemap| tilemap.tiles.get(&**position))
+            {
+                info!("Map feature destroyed at {:?}", position);
+                commands.entity(*tile).insert(EmptyTile);
+            }
+        }
+    }
+
+    fn clear_rubble(
+        mut commands: Commands,
+        games: Query<(Entity, &GamePhase, &Turn, Option<&TileOccupancy>), Changed<GamePhase>>,
+        rubble: Query<(Entity, &Position, &RubbleTile, &InGame)>,
+        tilemaps: Query<&TilemapEntities>,
+    ) {
+        for (game, _, turn, occupancy) in games
+            .iter()
+            .filter(|(_, phase, _, _)| matches!(phase, GamePhase::Choose))
+        {
+            for (entity, position, _, _) in rubble
+                .iter()
+                .filter(|(_, _, tile, in_game)| ***in_game == game && **turn >= tile.clears_on_turn)
+            {
+                info!("Rubble cleared at {:?}", position);
+                commands.entity(entity).despawn_recursive();
+
+                // Another tile may have been stacked onto the rubble in the meantime
+                let shared = occupancy.is_some_and(|occupancy| {
+                    occupancy
+                        .occupants(position)
+                        .any(|occupant| occupant != entity)
+                });
+                if shared {
+                    continue;
+                }
+                if let Some(tile) = tilemaps
+                    .iter()
+                    .find_map(|tilemap| tilemap.tiles.get(&**position))
+                {
+                    commands.entity(*tile).insert(EmptyTile);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct RubbleTile {
+    clears_on_turn: usize,
+}
+
+impl RubbleTile {
+    // Number of act phases the rubble blocks before it clears
+    pub const TURNS: usize = 2;
+}
+
+impl Tile for RubbleTile {
+    const TEXTURE: Option<&'static str> = Some("tiles/rubble.png");
+    fn spawn(position: Position, _player: Entity, game: Entity) -> impl Command {
+        RubbleSpawn { position, game }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        ColorMaterial::from_color(Color::Srgba(palettes::css::GRAY))
+    }
+
+    fn activate(
+        &self,
+        entity: Entity,
+        parameters: TileParameters,
+        _shooter: Option<Entity>,
+    ) -> impl Command {
+        RubbleActivate {
+            tile: entity,
+            position: parameters.position,
+            vulnerable: Self::vulnerable(&parameters),
+        }
+    }
+
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
+}
+
+pub struct RubbleSpawn {
+    position: Position,
+    game: Entity,
+}
+
+impl Command for RubbleSpawn {
+    fn apply(self, world: &mut World) {
+        let turn = world.get::<Turn>(self.game).copied().unwrap_or_default();
+        world.spawn((
+            RubbleTile {
+                clears_on_turn: *turn + RubbleTile::TURNS + 1,
+            },
+            self.position,
+            InGame::new(self.game),
+        ));
+    }
+}
+
+pub struct RubbleActivate {
+    tile: Entity,
+    position: Position,
+    vulnerable: Vec<Direction>,
+}
+
+impl Command for RubbleActivate {
+    fn apply(self, world: &mut World) {
+        world.spawn(Consumption::bundle(
+            self.tile,
+            self.vulnerable,
+            self.position,
+        ));
+    }
+}
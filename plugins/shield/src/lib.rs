@@ -0,0 +1,283 @@
+use bevy::{color::palettes, ecs::world::Command, prelude::*};
+
+use game_loop::InGame;
+use merchandise::{MerchAppExt, Merchandise, Money};
+use popups::PopupEvent;
+use tilemap::{Tile as MapTile, TileBundle, TilemapEntities, TilemapLayout};
+use tiles::{
+    lasers::{Consumption, Direction, LaserKind, Position, ShieldField, ShieldHitEvent},
+    tile_material, DamageTile, Owner, Tile, TileParameters, TilePlugin,
+};
+
+pub struct ShieldPlugin;
+
+impl Plugin for ShieldPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(TilePlugin::<ShieldTile>::default())
+            .add_systems(
+                Update,
+                (
+                    Self::spawn_overlays,
+                    (Self::drain_overlays, Self::fade_overlays).chain(),
+                    Self::despawn_overlays,
+                    Self::show_absorbed,
+                ),
+            );
+        app.define_merchandise::<ShieldTile>();
+    }
+}
+
+impl ShieldPlugin {
+    // The overlay belongs to the generator rather than its per-act field, so it stays on the map
+    // between acts
+    fn spawn_overlays(
+        mut commands: Commands,
+        shields: Query<(Entity, &Position), Added<ShieldTile>>,
+        layouts: Query<&TilemapLayout>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        let Ok(layout) = layouts.get_single() else {
+            return;
+        };
+        for (entity, position) in &shields {
+            let mesh = meshes.add(MapTile::mesh(layout));
+            let material = materials.add(ShieldOverlay::material());
+            for hex in position.range(ShieldTile::RADIUS) {
+                commands.spawn(TileBundle::new(
+                    ShieldOverlay {
+                        tile: entity,
+                        drained: false,
+                    },
+                    layout.hex_to_world_pos(hex),
+                    12.,
+                    mesh.clone(),
+                    material.clone(),
+                ));
+            }
+        }
+    }
+
+    // A drained shield stays visible but no longer protects anything until its field is
+    // recharged in the next act
+    fn drain_overlays(
+        fields: Query<&ShieldField, Changed<ShieldField>>,
+        mut overlays: Query<&mut ShieldOverlay>,
+    ) {
+        for field in &fields {
+            let drained = field.capacity == 0;
+            for mut overlay in overlays
+                .iter_mut()
+                .filter(|overlay| overlay.tile == field.tile && overlay.drained != drained)
+            {
+                overlay.drained = drained;
+            }
+        }
+    }
+
+    fn fade_overlays(
+        overlays: Query<(&ShieldOverlay, &Handle<ColorMaterial>), Changed<ShieldOverlay>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        for (overlay, material) in &overlays {
+            if let Some(material) = materials.get_mut(material) {
+                material.color.set_alpha(overlay.alpha());
+            }
+        }
+    }
+
+    fn show_absorbed(
+        mut commands: Commands,
+        mut shield_hits: EventReader<ShieldHitEvent>,
+        tilemaps: Query<&TilemapEntities>,
+    ) {
+        for ShieldHitEvent {
+            position, absorbed, ..
+        } in shield_hits.read()
+        {
+            if let Some(tile) = tilemaps
+                .iter()
+                .find_map(|tilemap| tilemap.tiles.get(&**position))
+            {
+                commands.trigger_targets(
+                    PopupEvent {
+                        text: format!("({})", absorbed),
+                    },
+                    *tile,
+                );
+            }
+        }
+    }
+
+    fn despawn_overlays(
+        mut commands: Commands,
+        overlays: Query<(Entity, &ShieldOverlay)>,
+        shields: Query<(), With<ShieldTile>>,
+    ) {
+        for (entity, overlay) in &overlays {
+            if shields.get(overlay.tile).is_err() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct ShieldTile;
+
+impl ShieldTile {
+    // Hexes around the generator covered by its shield
+    pub const RADIUS: u32 = 1;
+    // Strength the shield can absorb per level before it runs dry
+    pub const CAPACITY: usize = 2;
+}
+
+impl Tile for ShieldTile {
+    const MAX_HEALTH: Option<usize> = Some(2);
+    const TEXTURE: Option<&'static str> = Some("tiles/shield.png");
+
+    fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
+        ShieldSpawn { position, player }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        Color::Srgba(palettes::css::STEEL_BLUE).into()
+    }
+
+    fn activate(
+        &self,
+        entity: Entity,
+        parameters: TileParameters,
+        shooter: Option<Entity>,
+    ) -> impl Command {
+        ShieldActivate {
+            tile: entity,
+            owner: shooter.unwrap_or_else(|| panic!("Shield needs to have an owner to protect")),
+            position: parameters.position,
+            capacity: Self::CAPACITY * *parameters.level as usize,
+            vulnerable: Self::vulnerable(&parameters),
+        }
+    }
+
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
+
+    // The shield drains before the generator itself takes damage
+    fn on_hit(
+        &self,
+        entity: Entity,
+        strength: usize,
+        _shooter: Entity,
+        _kind: LaserKind,
+    ) -> Option<impl Command> {
+        Some(ShieldOnHit {
+            tile: entity,
+            strength,
+        })
+    }
+}
+
+impl Merchandise for ShieldTile {
+    const KEY: &'static str = "shield";
+    const PRICE: Money = Money::new(6);
+    const NAME: &'static str = "Shield Generator";
+    const UPGRADABLE: bool = true;
+
+    fn material(asset_server: &AssetServer) -> ColorMaterial {
+        let mut base = tile_material::<Self>(asset_server, 0.);
+        base.color.set_alpha(0.6);
+        base
+    }
+}
+
+pub struct ShieldSpawn {
+    position: Position,
+    player: Entity,
+}
+
+impl Command for ShieldSpawn {
+    fn apply(self, world: &mut World) {
+        if let Some(game) = world.get::<InGame>(self.player) {
+            world.spawn((
+                ShieldTile,
+                self.position,
+                Owner::new(self.player),
+                game.clone(),
+            ));
+        }
+    }
+}
+
+pub struct ShieldActivate {
+    tile: Entity,
+    owner: Entity,
+    position: Position,
+    capacity: usize,
+    vulnerable: Vec<Direction>,
+}
+
+impl Command for ShieldActivate {
+    fn apply(self, world: &mut World) {
+        world.spawn((
+            ShieldField {
+                tile: self.tile,
+                owner: self.owner,
+                center: self.position,
+                radius: ShieldTile::RADIUS,
+                capacity: self.capacity,
+            },
+            Consumption::bundle(self.tile, self.vulnerable, self.position),
+        ));
+    }
+}
+
+pub struct ShieldOnHit {
+    tile: Entity,
+    strength: usize,
+}
+
+impl Command for ShieldOnHit {
+    fn apply(self, world: &mut World) {
+        let mut fields = world.query::<&mut ShieldField>();
+        let absorbed = fields
+            .iter_mut(world)
+            .find(|field| field.tile == self.tile)
+            .map_or(0, |mut field| field.drain(self.strength));
+
+        if absorbed < self.strength {
+            DamageTile {
+                tile: self.tile,
+                strength: self.strength - absorbed,
+            }
+            .apply(world);
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct ShieldOverlay {
+    tile: Entity,
+    drained: bool,
+}
+
+impl ShieldOverlay {
+    const ALPHA: f32 = 0.25;
+    const DRAINED_ALPHA: f32 = 0.05;
+
+    fn alpha(&self) -> f32 {
+        if self.drained {
+            Self::DRAINED_ALPHA
+        } else {
+            Self::ALPHA
+        }
+    }
+
+    fn material() -> ColorMaterial {
+        let mut material = ColorMaterial::from_color(Color::Srgba(palettes::css::LIGHT_SKY_BLUE));
+        material.color.set_alpha(Self::ALPHA);
+        material
+    }
+}
@@ -9,21 +9,25 @@ use bevy::{
 use sickle_ui::{
     prelude::{
         LabelConfig, RadioGroup, UiBuilderExt, UiColumnExt, UiContainerExt, UiLabelExt,
-        UiRadioGroupExt,
+        UiRadioGroupExt, UiRowExt,
     },
     ui_style::generated::{SetFlexDirectionExt, SetMaxHeightExt, SetOverflowExt},
     SickleUiPlugin,
 };
 
+use events::{EnvironmentEventKind, EnvironmentSettings};
 use game_loop::{GamePhase, GamePlayers, Player, Ready};
-use merchandise::{Merch, MerchMaterials, MerchRegistry, Purchase};
+use merchandise::{
+    ActionHistory, HistoryAction, Merch, MerchMaterials, MerchRegistry, Money, Orientation,
+    Purchase, PurchaseFailed, Redo, Reorient, Sell, Undo, Upgrade,
+};
 use tilemap::{
-    CursorDirection, CursorWorldPosition, EmptyTile, EmptyTileMaterial, TargetedTile,
-    TerritoryTileMaterial, Tile,
+    CursorDirection, CursorWorldPosition, EmptyTile, SetTileLayer, TargetedTile, Tile, TileLayer,
+    TilemapEntities,
 };
 use tiles::{
-    lasers::{Direction, Position, Rotation},
-    Territory,
+    lasers::{Direction, LaserKind, Position, Rotation, YReflection},
+    Level, Owner,
 };
 
 pub struct ShopPlugin;
@@ -38,24 +42,19 @@ impl Plugin for ShopPlugin {
                 Self::setup_ui,
                 Self::handle_shop_selection,
                 Self::handle_ready,
+                Self::handle_tower_actions,
+                Self::handle_history_buttons,
                 Self::handle_player_control,
                 Self::capture_cursor.run_if(resource_exists::<CursorCapture>),
-                Self::render_territories.run_if(
-                    resource_exists::<TerritoryTileMaterial>
-                        .and_then(resource_exists::<EmptyTileMaterial>)
-                        .and_then(resource_exists::<ControllingPlayer>),
-                ),
-                Self::update_tile_material.run_if(
-                    resource_exists_and_changed::<SelectedMerch>
-                        .or_else(resource_removed::<SelectedMerch>())
-                        .or_else(resource_exists_and_changed::<TargetedTile>)
-                        .or_else(resource_removed::<TargetedTile>()),
-                ),
+                Self::update_tile_preview,
                 Self::make_purchase.run_if(
                     resource_exists::<SelectedMerch>
                         .and_then(resource_exists::<TargetedTile>)
                         .and_then(resource_exists::<ControllingPlayer>),
                 ),
+                Self::select_tower.run_if(
+                    resource_exists::<TargetedTile>.and_then(resource_exists::<ControllingPlayer>),
+                ),
                 Self::clear_shop,
                 Self::spawn_drag_markers,
                 Self::update_old_purchases,
@@ -63,10 +62,30 @@ impl Plugin for ShopPlugin {
                 Self::start_drag.run_if(resource_exists::<CursorWorldPosition>),
                 Self::handle_drag,
                 Self::stop_drag,
+                Self::sync_tile_adjusters,
             )
                 .chain()
                 .in_set(ShopSystems),
         );
+        app.add_systems(
+            Update,
+            (
+                (
+                    Self::expire_alerts,
+                    Self::clear_alert_panels,
+                    Self::show_alerts.run_if(resource_exists::<ControllingPlayer>),
+                )
+                    .chain(),
+                Self::sync_laser_kind_switch
+                    .run_if(resource_exists_and_changed::<ControllingPlayer>)
+                    .before(Self::handle_laser_kind_selection),
+                Self::handle_laser_kind_selection.run_if(resource_exists::<ControllingPlayer>),
+                Self::handle_event_chance_selection.run_if(resource_exists::<EnvironmentSettings>),
+                Self::handle_event_toggles.run_if(resource_exists::<EnvironmentSettings>),
+                Self::update_tile_selection,
+            )
+                .in_set(ShopSystems),
+        );
     }
 }
 
@@ -75,6 +94,9 @@ impl ShopPlugin {
         mut commands: Commands,
         games: Query<(&GamePhase, &GamePlayers), Or<(Changed<GamePhase>, Added<GamePlayers>)>>,
         merch_registry: Res<MerchRegistry>,
+        controlling_player: Option<Res<ControllingPlayer>>,
+        laser_kinds: Query<&LaserKind>,
+        environment: Option<Res<EnvironmentSettings>>,
     ) {
         if let Some((_, players)) = games
             .get_single()
@@ -90,7 +112,7 @@ impl ShopPlugin {
                     let merch = merch_registry
                         .sorted()
                         .into_iter()
-                        .map(|(_, merch)| merch.name())
+                        .map(|(_, merch)| format!("{} ({})", merch.name(), merch.price()))
                         .collect();
                     column
                         .radio_group(merch, None, false)
@@ -109,6 +131,68 @@ impl ShopPlugin {
                         .max_height(Val::Percent(100.))
                         .overflow(Overflow::clip_y())
                         .flex_direction(FlexDirection::Row);
+                    let selected_kind = controlling_player
+                        .as_deref()
+                        .and_then(|player| laser_kinds.get(**player).ok())
+                        .copied()
+                        .unwrap_or_default();
+                    let kind_labels = LaserKind::ALL
+                        .iter()
+                        .map(|kind| format!("{} laser", kind.label()))
+                        .collect::<Vec<_>>();
+                    column
+                        .radio_group(
+                            kind_labels,
+                            LaserKind::ALL
+                                .iter()
+                                .position(|kind| *kind == selected_kind),
+                            false,
+                        )
+                        .insert(ShopLaserKindSwitch)
+                        .style()
+                        .max_height(Val::Percent(100.))
+                        .overflow(Overflow::clip_y())
+                        .flex_direction(FlexDirection::Row);
+                    // Environment events can only be tuned when the game rolls them at all
+                    if let Some(environment) = environment.as_deref() {
+                        let chance_labels = ShopEventChanceSwitch::CHANCES
+                            .iter()
+                            .map(|(label, _)| label.to_string())
+                            .collect::<Vec<_>>();
+                        column
+                            .radio_group(
+                                chance_labels,
+                                ShopEventChanceSwitch::selected(environment.chance),
+                                false,
+                            )
+                            .insert(ShopEventChanceSwitch)
+                            .style()
+                            .max_height(Val::Percent(100.))
+                            .overflow(Overflow::clip_y())
+                            .flex_direction(FlexDirection::Row);
+                        column.row(|row| {
+                            for kind in EnvironmentEventKind::ALL {
+                                let toggle = EventToggle(kind);
+                                row.container(
+                                    ButtonBundle {
+                                        style: Style {
+                                            width: Val::Percent(25.),
+                                            height: Val::Px(30.),
+                                            justify_content: JustifyContent::Center,
+                                            align_items: AlignItems::Center,
+                                            ..default()
+                                        },
+                                        background_color: toggle.color(environment).into(),
+                                        ..default()
+                                    },
+                                    |container| {
+                                        container.label(LabelConfig::from(kind.name()));
+                                    },
+                                )
+                                .insert(toggle);
+                            }
+                        });
+                    }
                     column
                         .container(
                             ButtonBundle {
@@ -128,12 +212,130 @@ impl ShopPlugin {
                             },
                         )
                         .insert(ReadyButton);
+                    column.row(|row| {
+                        for action in [TowerAction::Sell, TowerAction::Upgrade] {
+                            row.container(
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Percent(50.),
+                                        height: Val::Px(30.),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    background_color: action.color().into(),
+                                    ..default()
+                                },
+                                |container| {
+                                    container.label(LabelConfig::from(action.label()));
+                                },
+                            )
+                            .insert(action);
+                        }
+                    });
+                    column.row(|row| {
+                        for button in [HistoryButton::Undo, HistoryButton::Redo] {
+                            row.container(
+                                ButtonBundle {
+                                    style: Style {
+                                        width: Val::Percent(50.),
+                                        height: Val::Px(30.),
+                                        justify_content: JustifyContent::Center,
+                                        align_items: AlignItems::Center,
+                                        ..default()
+                                    },
+                                    background_color: HistoryButton::color().into(),
+                                    ..default()
+                                },
+                                |container| {
+                                    container.label(LabelConfig::from(button.label()));
+                                },
+                            )
+                            .insert(button);
+                        }
+                    });
                 })
                 .style()
                 .max_height(Val::Percent(100.));
         }
     }
 
+    // Failed purchases of the controlling player are shown as toasts until they expire
+    fn show_alerts(
+        mut commands: Commands,
+        mut failures: EventReader<PurchaseFailed>,
+        controlling_player: Res<ControllingPlayer>,
+        panels: Query<Entity, With<AlertPanel>>,
+    ) {
+        let errors = failures
+            .read()
+            .filter(|failure| failure.player == **controlling_player)
+            .map(|failure| failure.error.to_string())
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            return;
+        }
+
+        // Toasts of the same frame share one panel
+        let panel = panels
+            .iter()
+            .next()
+            .unwrap_or_else(|| commands.spawn(AlertPanel::bundle()).id());
+        for error in errors {
+            let toast = commands
+                .spawn((
+                    Alert(Timer::from_seconds(Alert::DURATION, TimerMode::Once)),
+                    NodeBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(8.)),
+                            margin: UiRect::top(Val::Px(6.)),
+                            ..default()
+                        },
+                        border_radius: BorderRadius::all(Val::Px(4.)),
+                        background_color: Color::Srgba(palettes::css::DARK_RED.with_alpha(0.9))
+                            .into(),
+                        ..default()
+                    },
+                ))
+                .with_children(|toast| {
+                    toast.spawn(TextBundle::from_section(
+                        error,
+                        TextStyle {
+                            font_size: 18.,
+                            color: Color::WHITE,
+                            ..default()
+                        },
+                    ));
+                })
+                .id();
+            commands.entity(panel).add_child(toast);
+        }
+    }
+
+    fn expire_alerts(
+        mut commands: Commands,
+        time: Res<Time>,
+        mut alerts: Query<(Entity, &mut Alert)>,
+    ) {
+        for (entity, mut alert) in &mut alerts {
+            if alert.tick(time.delta()).just_finished() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+
+    // The panel goes away with its last toast and is spawned again for the next failure
+    fn clear_alert_panels(
+        mut commands: Commands,
+        panels: Query<(Entity, Option<&Children>), With<AlertPanel>>,
+    ) {
+        for (entity, children) in &panels {
+            if children.is_none_or(|children| children.is_empty()) {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+
     fn clear_shop(
         mut commands: Commands,
         games: Query<&GamePhase>, // Not working with Changed filter for some reason...
@@ -148,6 +350,7 @@ impl ShopPlugin {
             }
 
             commands.remove_resource::<SelectedMerch>();
+            commands.remove_resource::<SelectedTower>();
         };
     }
 
@@ -206,6 +409,85 @@ impl ShopPlugin {
         }
     }
 
+    // The switch shows the kind of whichever player is being controlled
+    fn sync_laser_kind_switch(
+        controlling_player: Res<ControllingPlayer>,
+        laser_kinds: Query<&LaserKind>,
+        mut kind_switch: Query<&mut RadioGroup, With<ShopLaserKindSwitch>>,
+    ) {
+        let Ok(mut kind_switch) = kind_switch.get_single_mut() else {
+            return;
+        };
+        let kind = laser_kinds
+            .get(**controlling_player)
+            .copied()
+            .unwrap_or_default();
+        if let Some(index) = LaserKind::ALL.iter().position(|option| *option == kind) {
+            if kind_switch.selected() != Some(index) {
+                kind_switch.select(index);
+            }
+        }
+    }
+
+    // The chosen kind is kept on the player and given to the laser towers they buy
+    fn handle_laser_kind_selection(
+        mut commands: Commands,
+        controlling_player: Res<ControllingPlayer>,
+        kind_switch: Query<&RadioGroup, (With<ShopLaserKindSwitch>, Changed<RadioGroup>)>,
+    ) {
+        let Ok(kind_switch) = kind_switch.get_single() else {
+            return;
+        };
+        if let Some(kind) = kind_switch
+            .selected()
+            .and_then(|index| LaserKind::ALL.get(index))
+        {
+            info!(
+                "Player {} chose {} lasers",
+                **controlling_player,
+                kind.label()
+            );
+            commands.entity(**controlling_player).insert(*kind);
+        }
+    }
+
+    fn handle_event_chance_selection(
+        mut environment: ResMut<EnvironmentSettings>,
+        chance_switch: Query<&RadioGroup, (With<ShopEventChanceSwitch>, Changed<RadioGroup>)>,
+    ) {
+        let Ok(chance_switch) = chance_switch.get_single() else {
+            return;
+        };
+        if let Some((label, chance)) = chance_switch
+            .selected()
+            .and_then(|index| ShopEventChanceSwitch::CHANCES.get(index))
+        {
+            info!("Environment events set to {}", label);
+            environment.chance = *chance;
+        }
+    }
+
+    fn handle_event_toggles(
+        mut interactions: Query<
+            (&mut BackgroundColor, &Interaction, &EventToggle),
+            Changed<Interaction>,
+        >,
+        mut environment: ResMut<EnvironmentSettings>,
+    ) {
+        for (mut color, interaction, toggle) in &mut interactions {
+            if let Interaction::Pressed = interaction {
+                let enabled = !environment.is_enabled(**toggle);
+                environment.set_enabled(**toggle, enabled);
+                info!("{} enabled: {}", toggle.name(), enabled);
+            }
+            *color = match interaction {
+                Interaction::Hovered => toggle.color(&environment).lighter(0.2),
+                _ => toggle.color(&environment),
+            }
+            .into();
+        }
+    }
+
     fn handle_ready(
         mut commands: Commands,
         mut interactions: Query<
@@ -233,97 +515,155 @@ impl ShopPlugin {
         }
     }
 
-    fn render_territories(
+    fn handle_tower_actions(
+        mut commands: Commands,
+        mut interactions: Query<
+            (&mut BackgroundColor, &Interaction, &TowerAction),
+            Changed<Interaction>,
+        >,
+        mut sales: EventWriter<Sell>,
+        mut upgrades: EventWriter<Upgrade>,
+        selected_tower: Option<Res<SelectedTower>>,
+        levels: Query<Option<&Level>>,
         controlling_player: Option<Res<ControllingPlayer>>,
-        territories: Query<&Territory>,
-        mut tile_materials: Query<(Entity, &mut Handle<ColorMaterial>), With<EmptyTile>>,
-        territory_tile_material: Res<TerritoryTileMaterial>,
-        empty_tile_material: Res<EmptyTileMaterial>,
-        mut last_controlling_player: Local<Option<ControllingPlayer>>,
     ) {
-        if let Some(territory) = controlling_player
-            .as_ref()
-            .and_then(|player| territories.get(***player).ok())
-        {
-            for (_, mut material) in tile_materials
-                .iter_mut()
-                .filter(|(tile, _)| territory.contains(tile))
-            {
-                *material = territory_tile_material.clone();
+        for (mut color, interaction, action) in &mut interactions {
+            match interaction {
+                Interaction::Pressed => {
+                    *color = action.color().darker(0.2).into();
+                    let (Some(selected), Some(player)) =
+                        (selected_tower.as_deref(), controlling_player.as_deref())
+                    else {
+                        continue;
+                    };
+                    match action {
+                        TowerAction::Sell => {
+                            sales.send(Sell {
+                                seller: **player,
+                                tile: selected.tile,
+                            });
+                            commands.remove_resource::<SelectedTower>();
+                        }
+                        TowerAction::Upgrade => {
+                            let current = levels
+                                .get(selected.tower)
+                                .ok()
+                                .flatten()
+                                .copied()
+                                .unwrap_or_default();
+                            if let Some(level) = current.next() {
+                                upgrades.send(Upgrade {
+                                    buyer: **player,
+                                    tile: selected.tile,
+                                    level,
+                                });
+                            }
+                        }
+                    }
+                }
+                Interaction::Hovered => {
+                    *color = action.color().lighter(0.2).into();
+                }
+                Interaction::None => {
+                    *color = action.color().into();
+                }
             }
         }
+    }
 
-        if controlling_player.as_deref().cloned() != *last_controlling_player {
-            if let Some(territory) = last_controlling_player
-                .as_ref()
-                .and_then(|player| territories.get(**player).ok())
-            {
-                for (_, mut material) in tile_materials
-                    .iter_mut()
-                    .filter(|(tile, _)| territory.contains(tile))
-                {
-                    *material = empty_tile_material.clone();
+    fn handle_history_buttons(
+        mut interactions: Query<
+            (&mut BackgroundColor, &Interaction, &HistoryButton),
+            Changed<Interaction>,
+        >,
+        mut undos: EventWriter<Undo>,
+        mut redos: EventWriter<Redo>,
+        controlling_player: Option<Res<ControllingPlayer>>,
+    ) {
+        for (mut color, interaction, button) in &mut interactions {
+            match interaction {
+                Interaction::Pressed => {
+                    *color = HistoryButton::color().darker(0.2).into();
+                    let Some(player) = controlling_player.as_deref() else {
+                        continue;
+                    };
+                    match button {
+                        HistoryButton::Undo => {
+                            undos.send(Undo { player: **player });
+                        }
+                        HistoryButton::Redo => {
+                            redos.send(Redo { player: **player });
+                        }
+                    }
+                }
+                Interaction::Hovered => {
+                    *color = HistoryButton::color().lighter(0.2).into();
+                }
+                Interaction::None => {
+                    *color = HistoryButton::color().into();
                 }
             }
         }
-
-        *last_controlling_player = controlling_player.as_deref().cloned();
     }
 
-    fn update_tile_material(
-        mut tile_materials: Query<&mut Handle<ColorMaterial>>,
+    // Previews the selected merch on the targeted tile while it is still empty
+    fn update_tile_preview(
+        mut commands: Commands,
         merch_materials: Res<MerchMaterials>,
         selected_merch: Option<Res<SelectedMerch>>,
         targeted_tile: Option<Res<TargetedTile>>,
         empty_tiles: Query<&EmptyTile>,
-        empty_tile_material: Res<EmptyTileMaterial>,
-        mut last_target: Local<Option<TargetedTile>>,
-        mut last_merch: Local<Option<SelectedMerch>>,
+        mut last_preview: Local<Option<(Entity, Handle<ColorMaterial>)>>,
     ) {
-        if let Some(targeted_tile) = targeted_tile
+        let preview = targeted_tile
             .as_deref()
             .filter(|targeted_tile| empty_tiles.contains(targeted_tile.tile))
-        {
-            if let Ok(mut tile_material) = tile_materials.get_mut(targeted_tile.tile) {
-                if let Some(merch_material) = selected_merch
+            .zip(
+                selected_merch
                     .as_deref()
-                    .and_then(|merch| merch_materials.get(&merch.id()))
-                {
-                    *tile_material = merch_material.clone();
-                }
-            }
+                    .and_then(|merch| merch_materials.get(&merch.id())),
+            )
+            .map(|(targeted_tile, material)| (targeted_tile.tile, material.clone()));
+        if preview == *last_preview {
+            return;
         }
-        // todo: check that tile isn't occupied
-        // Only checking for empty tiles and returning them to empty material, but filled tiles are
-        // changed permanently - this is the bug
-        // Remove resource run condition doesn't seem to be working - not running in final tick after
-        // `TargetedTile` is removed
-        if targeted_tile.as_deref().cloned() != *last_target {
-            if let Some(target) = last_target
-                .as_ref()
-                .filter(|last_target| empty_tiles.contains(last_target.tile))
-            {
-                if let Ok(mut tile_material) = tile_materials.get_mut(target.tile) {
-                    *tile_material = empty_tile_material.clone();
-                }
-            }
+
+        if let Some((tile, _)) = last_preview.take() {
+            commands.add(SetTileLayer::clear(tile, TileLayer::Preview));
         }
-        if let Some(targeted_tile) = targeted_tile
-            .as_deref()
-            .filter(|targeted_tile| empty_tiles.contains(targeted_tile.tile))
-        {
-            let selected_merch = selected_merch.as_deref();
-            if selected_merch != last_merch.as_ref() {
-                if let Ok(mut tile_material) = tile_materials.get_mut(targeted_tile.tile) {
-                    *tile_material = selected_merch
-                        .and_then(|selected_merch| merch_materials.get(&selected_merch.id()))
-                        .cloned()
-                        .unwrap_or_else(|| empty_tile_material.clone());
-                }
-            }
+        if let Some((tile, material)) = &preview {
+            commands.add(SetTileLayer::new(
+                *tile,
+                TileLayer::Preview,
+                material.clone(),
+            ));
         }
-        *last_target = targeted_tile.as_deref().cloned();
-        *last_merch = selected_merch.as_deref().cloned();
+        *last_preview = preview;
+    }
+
+    // Highlights the hex of the selected tower
+    fn update_tile_selection(
+        mut commands: Commands,
+        selected_tower: Option<Res<SelectedTower>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+        mut selection_material: Local<Option<Handle<ColorMaterial>>>,
+        mut last_selected: Local<Option<Entity>>,
+    ) {
+        let selected = selected_tower.as_deref().map(|selected| selected.tile);
+        if selected == *last_selected {
+            return;
+        }
+
+        if let Some(tile) = last_selected.take() {
+            commands.add(SetTileLayer::clear(tile, TileLayer::Selection));
+        }
+        if let Some(tile) = selected {
+            let material = selection_material
+                .get_or_insert_with(|| materials.add(Color::srgba(1., 0.84, 0., 0.35)))
+                .clone();
+            commands.add(SetTileLayer::new(tile, TileLayer::Selection, material));
+        }
+        *last_selected = selected;
     }
 
     fn capture_cursor(
@@ -389,6 +729,75 @@ impl ShopPlugin {
         }
     }
 
+    // Right clicking a tower bought in an earlier turn selects it for selling, upgrading or
+    // reorienting
+    #[allow(clippy::type_complexity, clippy::too_many_arguments)]
+    fn select_tower(
+        mut commands: Commands,
+        mouse_input: Res<ButtonInput<MouseButton>>,
+        targeted_tile: Res<TargetedTile>,
+        controlling_player: Res<ControllingPlayer>,
+        selected_tower: Option<Res<SelectedTower>>,
+        capture: Option<Res<CursorCapture>>,
+        hexes: Query<&Tile>,
+        towers: Query<
+            (
+                Entity,
+                &Position,
+                &Owner,
+                Option<&Direction>,
+                Option<&Rotation>,
+                Option<&YReflection>,
+            ),
+            (With<Merch>, Without<JustPurchased>),
+        >,
+        markers: Query<(Entity, &Parent), With<TileAdjuster>>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        if capture.is_some_and(|capture| capture.0)
+            || !mouse_input.just_released(MouseButton::Right)
+        {
+            return;
+        }
+
+        if let Some(selected) = selected_tower {
+            for (marker, _) in markers
+                .iter()
+                .filter(|(_, parent)| ***parent == selected.tower)
+            {
+                commands.entity(marker).despawn();
+            }
+            commands.remove_resource::<SelectedTower>();
+        }
+
+        let Ok(hex) = hexes.get(targeted_tile.tile) else {
+            return;
+        };
+        let Some((tower, _, _, direction, rotation, tilt)) =
+            towers.iter().find(|(_, position, owner, ..)| {
+                ***position == **hex && ***owner == **controlling_player
+            })
+        else {
+            return;
+        };
+
+        info!("Selected tower on tile {:?}", targeted_tile.tile);
+        commands.insert_resource(SelectedTower {
+            tower,
+            tile: targeted_tile.tile,
+        });
+        let orientation = Orientation::new(direction, rotation, tilt);
+        if orientation != Orientation::default() {
+            let mut marker = TileAdjuster::spawn(&mut meshes, &mut materials);
+            if let Some(translation) = TileAdjuster::orientation_translation(orientation) {
+                marker.transform.translation = translation;
+            }
+            let marker = commands.spawn((TileAdjuster, marker)).id();
+            commands.entity(tower).add_child(marker);
+        }
+    }
+
     fn spawn_drag_markers(
         mut commands: Commands,
         purchases: Query<(Entity, &Children), With<JustPurchased>>,
@@ -430,15 +839,27 @@ impl ShopPlugin {
             for purchase in &purchases {
                 commands.entity(purchase).remove::<JustPurchased>();
             }
+
+            commands.remove_resource::<SelectedTower>();
         }
     }
 
+    // Towers bought this turn can be adjusted for free, older towers are reoriented through
+    // `Reorient` once the drag stops
+    #[allow(clippy::type_complexity)]
     fn update_tile_parameters(
         tile_adjusters: Query<(&Parent, &Transform), (Changed<Transform>, With<TileAdjuster>)>,
-        mut tiles: Query<(Option<&mut Direction>, Option<&mut Rotation>), With<Position>>,
+        mut tiles: Query<
+            (
+                Option<&mut Direction>,
+                Option<&mut Rotation>,
+                Option<&mut YReflection>,
+            ),
+            (With<Position>, With<JustPurchased>),
+        >,
     ) {
         for (parent, transform) in &tile_adjusters {
-            if let Ok((direction, rotation)) = tiles.get_mut(**parent) {
+            if let Ok((direction, rotation, tilt)) = tiles.get_mut(**parent) {
                 if let Some(mut direction) = direction {
                     *direction = TileAdjuster::to_direction(transform.translation);
                 }
@@ -446,6 +867,10 @@ impl ShopPlugin {
                 if let Some(mut rotation) = rotation {
                     *rotation = TileAdjuster::to_rotation(transform.translation);
                 }
+
+                if let Some(mut tilt) = tilt {
+                    *tilt = TileAdjuster::to_tilt(transform.translation);
+                }
             }
         }
     }
@@ -454,21 +879,26 @@ impl ShopPlugin {
         mut commands: Commands,
         mouse_input: Res<ButtonInput<MouseButton>>,
         cursor_position: Res<CursorWorldPosition>,
-        markers: Query<(Entity, &GlobalTransform), With<TileAdjuster>>,
+        markers: Query<(Entity, &Parent, &GlobalTransform), With<TileAdjuster>>,
+        towers: Query<(Option<&Direction>, Option<&Rotation>, Option<&YReflection>)>,
     ) {
         if !mouse_input.just_pressed(MouseButton::Left) {
             return;
         }
 
-        if let Some((marker_entity, _)) = markers
+        if let Some((marker_entity, parent, _)) = markers
             .iter()
-            .filter(|(_, transform)| {
+            .filter(|(_, _, transform)| {
                 transform.translation().xy().distance(**cursor_position) <= TileAdjuster::RADIUS
             })
             .next()
         {
             info!("Dragging inserted in marker at current cursor position");
-            commands.entity(marker_entity).insert(Dragging);
+            let from = towers
+                .get(**parent)
+                .map(|(direction, rotation, tilt)| Orientation::new(direction, rotation, tilt))
+                .unwrap_or_default();
+            commands.entity(marker_entity).insert(Dragging { from });
         }
     }
 
@@ -487,29 +917,98 @@ impl ShopPlugin {
         };
 
         if let Some((_, cursor_direction)) = tiles.iter().find(|(&tile, _)| *tile == **position) {
-            let cursor_direction: Direction = (**cursor_direction).into();
-            let angle = match cursor_direction {
-                Direction::North => 0.,
-                Direction::Northwest => PI / 3.,
-                Direction::Southwest => 2. * PI / 3.,
-                Direction::South => PI,
-                Direction::Southeast => 4. * PI / 3.,
-                Direction::Northeast => 5. * PI / 3.,
-            };
-
-            let rotation = Quat::from_rotation_z(angle);
-            transform.translation = rotation.mul_vec3(TileAdjuster::OFFSET);
+            transform.translation = TileAdjuster::translation((**cursor_direction).into());
         }
     }
 
+    // Adjustments to towers bought this turn are free but still go into the owner's history
+    #[allow(clippy::type_complexity)]
     fn stop_drag(
         mut commands: Commands,
         mouse_input: Res<ButtonInput<MouseButton>>,
-        markers: Query<Entity, (With<TileAdjuster>, With<Dragging>)>,
+        markers: Query<(Entity, &Parent, &Transform, &Dragging), With<TileAdjuster>>,
+        towers: Query<(
+            &Position,
+            &Owner,
+            Option<&Direction>,
+            Option<&Rotation>,
+            Option<&YReflection>,
+            Has<JustPurchased>,
+        )>,
+        tilemaps: Query<&TilemapEntities>,
+        mut histories: Query<&mut ActionHistory>,
+        mut reorients: EventWriter<Reorient>,
     ) {
         if mouse_input.just_released(MouseButton::Left) {
-            for marker in &markers {
+            for (marker, parent, transform, dragging) in &markers {
                 commands.entity(marker).remove::<Dragging>();
+
+                let Ok((position, owner, direction, rotation, tilt, just_purchased)) =
+                    towers.get(**parent)
+                else {
+                    continue;
+                };
+                let to = TileAdjuster::to_orientation(
+                    Orientation::new(direction, rotation, tilt),
+                    transform.translation,
+                );
+                if just_purchased {
+                    if to != dragging.from {
+                        if let Ok(mut history) = histories.get_mut(**owner) {
+                            history.record(HistoryAction::Reorient {
+                                tower: **parent,
+                                from: dragging.from,
+                                to,
+                                fee: Money::default(),
+                            });
+                        }
+                    }
+                    continue;
+                }
+                let Some(tile) = tilemaps
+                    .iter()
+                    .find_map(|tilemap| tilemap.tiles.get(&**position))
+                else {
+                    continue;
+                };
+                reorients.send(Reorient {
+                    player: **owner,
+                    tile: *tile,
+                    direction: to.direction,
+                    rotation: to.rotation,
+                    tilt: to.tilt,
+                });
+            }
+        }
+    }
+
+    // Undoing or redoing a reorientation moves the tile adjuster back over the tower's
+    // orientation
+    #[allow(clippy::type_complexity)]
+    fn sync_tile_adjusters(
+        towers: Query<
+            (
+                &Children,
+                Option<&Direction>,
+                Option<&Rotation>,
+                Option<&YReflection>,
+            ),
+            Or<(Changed<Direction>, Changed<Rotation>, Changed<YReflection>)>,
+        >,
+        mut markers: Query<&mut Transform, (With<TileAdjuster>, Without<Dragging>)>,
+    ) {
+        for (children, direction, rotation, tilt) in &towers {
+            let Some(translation) =
+                TileAdjuster::orientation_translation(Orientation::new(direction, rotation, tilt))
+            else {
+                continue;
+            };
+            for child in children.iter() {
+                if let Ok(mut marker) = markers.get_mut(*child) {
+                    if marker.translation != translation {
+                        marker.translation = translation;
+                    }
+                }
             }
         }
     }
@@ -547,6 +1046,38 @@ impl ShopUIRoot {
 #[derive(Component, Reflect)]
 pub struct ShopMerchOption;
 
+#[derive(Debug)]
+#[derive(Component)]
+pub struct AlertPanel;
+
+impl AlertPanel {
+    fn bundle() -> impl Bundle {
+        (
+            Self,
+            Name::new("Alert Panel"),
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    right: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    flex_direction: FlexDirection::ColumnReverse,
+                    align_items: AlignItems::End,
+                    ..default()
+                },
+                ..default()
+            },
+        )
+    }
+}
+
+#[derive(Debug)]
+#[derive(Component, Deref, DerefMut)]
+pub struct Alert(Timer);
+
+impl Alert {
+    pub const DURATION: f32 = 4.;
+}
+
 #[derive(Clone, Debug, PartialEq)]
 #[derive(Deref, DerefMut, Resource, Reflect)]
 pub struct SelectedMerch(Merch);
@@ -555,6 +1086,57 @@ pub struct SelectedMerch(Merch);
 #[derive(Component)]
 pub struct ReadyButton;
 
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub enum TowerAction {
+    Sell,
+    Upgrade,
+}
+
+impl TowerAction {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Sell => "Sell",
+            Self::Upgrade => "Upgrade",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            Self::Sell => Color::Srgba(palettes::css::DARK_RED),
+            Self::Upgrade => Color::Srgba(palettes::css::SEA_GREEN),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub enum HistoryButton {
+    Undo,
+    Redo,
+}
+
+impl HistoryButton {
+    fn label(&self) -> &'static str {
+        match self {
+            Self::Undo => "Undo",
+            Self::Redo => "Redo",
+        }
+    }
+
+    fn color() -> Color {
+        Color::Srgba(palettes::css::DIM_GRAY)
+    }
+}
+
+// A tower bought in an earlier turn that the controlling player has selected on the map
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource)]
+pub struct SelectedTower {
+    pub tower: Entity,
+    pub tile: Entity,
+}
+
 #[derive(Debug, Default)]
 #[derive(Resource)]
 pub struct CursorCapture(pub bool);
@@ -563,6 +1145,48 @@ pub struct CursorCapture(pub bool);
 #[derive(Component)]
 pub struct ShopPlayerSwitch;
 
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct ShopLaserKindSwitch;
+
+#[derive(Clone, Debug)]
+#[derive(Component)]
+pub struct ShopEventChanceSwitch;
+
+impl ShopEventChanceSwitch {
+    // Probability of an environment event happening in a round
+    pub const CHANCES: [(&'static str, f64); 4] = [
+        ("No events", 0.),
+        ("Rare events", 0.1),
+        ("Some events", 0.3),
+        ("Frequent events", 0.6),
+    ];
+
+    // The option closest to the current chance, so settings made outside the shop still show
+    fn selected(chance: f64) -> Option<usize> {
+        (0..Self::CHANCES.len()).min_by(|a, b| {
+            (Self::CHANCES[*a].1 - chance)
+                .abs()
+                .total_cmp(&(Self::CHANCES[*b].1 - chance).abs())
+        })
+    }
+}
+
+// Turns one kind of environment event on or off
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Deref)]
+pub struct EventToggle(EnvironmentEventKind);
+
+impl EventToggle {
+    fn color(&self, environment: &EnvironmentSettings) -> Color {
+        if environment.is_enabled(**self) {
+            Color::Srgba(palettes::css::DARK_GOLDENROD)
+        } else {
+            Color::Srgba(palettes::css::DIM_GRAY)
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 #[derive(Deref, DerefMut, Resource, Reflect)]
 pub struct ControllingPlayer(Entity);
@@ -597,6 +1221,74 @@ impl TileAdjuster {
         }
     }
 
+    pub fn translation(direction: Direction) -> Vec3 {
+        let angle = match direction {
+            Direction::North => 0.,
+            Direction::Northwest => PI / 3.,
+            Direction::Southwest => 2. * PI / 3.,
+            Direction::South => PI,
+            Direction::Southeast => 4. * PI / 3.,
+            Direction::Northeast => 5. * PI / 3.,
+        };
+        Quat::from_rotation_z(angle).mul_vec3(Self::OFFSET)
+    }
+
+    // Each rotation sits in the middle of the sector that `to_rotation` maps to it
+    pub fn rotation_translation(rotation: Rotation) -> Vec3 {
+        let direction = match rotation.get() % 6 {
+            1 => Direction::Northeast,
+            2 => Direction::North,
+            3 => Direction::Northwest,
+            4 => Direction::Southwest,
+            5 => Direction::South,
+            _ => Direction::Southeast,
+        };
+        Self::translation(direction)
+    }
+
+    // Tilted towers only have two settings, on either side of the adjuster
+    pub fn tilt_translation(tilt: YReflection) -> Vec3 {
+        match tilt {
+            YReflection::LeftTilt => Self::translation(Direction::Northwest),
+            YReflection::RightTilt => Self::translation(Direction::Northeast),
+        }
+    }
+
+    // Where the adjuster sits for a tower, preferring its direction over its rotation or tilt
+    pub fn orientation_translation(orientation: Orientation) -> Option<Vec3> {
+        match orientation {
+            Orientation {
+                direction: Some(direction),
+                ..
+            } => Some(Self::translation(direction)),
+            Orientation {
+                rotation: Some(rotation),
+                ..
+            } => Some(Self::rotation_translation(rotation)),
+            Orientation {
+                tilt: Some(tilt), ..
+            } => Some(Self::tilt_translation(tilt)),
+            _ => None,
+        }
+    }
+
+    // Reads the parts of `current` that the tower has from where the adjuster was moved to
+    pub fn to_orientation(current: Orientation, position: Vec3) -> Orientation {
+        Orientation {
+            direction: current.direction.map(|_| Self::to_direction(position)),
+            rotation: current.rotation.map(|_| Self::to_rotation(position)),
+            tilt: current.tilt.map(|_| Self::to_tilt(position)),
+        }
+    }
+
+    pub fn to_tilt(position: Vec3) -> YReflection {
+        if position.x > 0. {
+            YReflection::RightTilt
+        } else {
+            YReflection::LeftTilt
+        }
+    }
+
     pub fn to_direction(position: Vec3) -> Direction {
         match position.angle_between(Vec3::X) {
             theta if theta < PI / 3. && theta >= 0. && position.y >= 0. => Direction::Northeast,
@@ -632,6 +1324,9 @@ impl TileAdjuster {
     }
 }
 
+// Remembers the orientation of the tower when the drag started so that it can be undone
 #[derive(Clone, Copy)]
 #[derive(Component)]
-pub struct Dragging;
+pub struct Dragging {
+    from: Orientation,
+}
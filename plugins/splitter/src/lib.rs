@@ -0,0 +1,200 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    color::palettes,
+    ecs::{system::SystemState, world::Command},
+    prelude::*,
+    sprite::MaterialMesh2dBundle,
+};
+
+use game_loop::InGame;
+use merchandise::{MerchAppExt, Merchandise, Money};
+use shop::JustPurchased;
+use tilemap::TilemapLayout;
+use tiles::{
+    lasers::{Consumption, Direction, Position, Splitting},
+    tile_material, Owner, Tile, TileParameters, TilePlugin,
+};
+
+pub struct SplitterPlugin;
+
+impl Plugin for SplitterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(TilePlugin::<SplitterTile>::default())
+            .add_systems(Update, Self::update_marker);
+        app.define_merchandise::<SplitterTile>();
+    }
+}
+
+impl SplitterPlugin {
+    fn update_marker(
+        tiles: Query<&Direction, (With<SplitterTile>, Changed<Direction>)>,
+        mut markers: Query<(&Parent, &mut Transform), With<SplitterMarker>>,
+    ) {
+        for (parent, mut transform) in &mut markers {
+            if let Ok(direction) = tiles.get(**parent) {
+                *transform = match *direction {
+                    Direction::North => Transform::IDENTITY,
+                    Direction::Northwest => {
+                        Transform::from_rotation(Quat::from_rotation_z(PI / 3.))
+                    }
+                    Direction::Southwest => {
+                        Transform::from_rotation(Quat::from_rotation_z(2. * PI / 3.))
+                    }
+                    Direction::South => Transform::from_rotation(Quat::from_rotation_z(PI)),
+                    Direction::Southeast => {
+                        Transform::from_rotation(Quat::from_rotation_z(4. * PI / 3.))
+                    }
+                    Direction::Northeast => {
+                        Transform::from_rotation(Quat::from_rotation_z(5. * PI / 3.))
+                    }
+                };
+                info!("Changed splitter marker transform");
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct SplitterTile;
+
+impl Tile for SplitterTile {
+    const MAX_HEALTH: Option<usize> = Some(2);
+    const TEXTURE: Option<&'static str> = Some("tiles/splitter.png");
+
+    fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
+        SplitterSpawn { position, player }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        Color::Srgba(palettes::css::MEDIUM_PURPLE).into()
+    }
+
+    fn activate(
+        &self,
+        entity: Entity,
+        parameters: TileParameters,
+        _shooter: Option<Entity>,
+    ) -> impl Command {
+        SplitterActivate {
+            tile: entity,
+            position: parameters.position,
+            direction: parameters
+                .direction
+                .unwrap_or_else(|| panic!("Splitter needs a direction")),
+            vulnerable: Self::vulnerable(&parameters),
+        }
+    }
+
+    // Lasers entering through the back are split, the front faces take the hits
+    fn vulnerable(parameters: &TileParameters) -> Vec<Direction> {
+        parameters
+            .direction
+            .map(|direction| direction.back_directions().to_vec())
+            .unwrap_or_default()
+    }
+}
+
+impl Merchandise for SplitterTile {
+    const KEY: &'static str = "splitter";
+    const PRICE: Money = Money::new(8);
+    const NAME: &'static str = "Beam Splitter";
+
+    fn material(asset_server: &AssetServer) -> ColorMaterial {
+        let mut base = tile_material::<Self>(asset_server, 0.);
+        base.color.set_alpha(0.6);
+        base
+    }
+}
+
+pub struct SplitterSpawn {
+    position: Position,
+    player: Entity,
+}
+
+impl Command for SplitterSpawn {
+    #[allow(clippy::type_complexity)]
+    fn apply(self, world: &mut World) {
+        let mut system_state: SystemState<(
+            Query<&TilemapLayout>,
+            ResMut<Assets<Mesh>>,
+            ResMut<Assets<ColorMaterial>>,
+        )> = SystemState::new(world);
+
+        let (layout, mut meshes, mut materials) = system_state.get_mut(world);
+
+        let Ok(translation) = layout
+            .get_single()
+            .map(|layout| layout.hex_to_world_pos(*self.position).extend(11.))
+        else {
+            info!("Did not get the single tilemap layout for the game");
+            return;
+        };
+
+        let stem = meshes.add(Rectangle::new(5., 20.));
+        let black = materials.add(Color::BLACK);
+        let prongs = meshes.add(Triangle2d::new(
+            Vec2::new(-18., 20.),
+            Vec2::new(18., 20.),
+            Vec2::new(0., 5.),
+        ));
+        let red = materials.add(Color::Srgba(bevy::color::palettes::css::RED));
+
+        if let Some(game) = world.get::<InGame>(self.player) {
+            world
+                .spawn((
+                    SplitterTile,
+                    self.position,
+                    Direction::default(),
+                    Owner::new(self.player),
+                    game.clone(),
+                    Transform::from_translation(translation),
+                    GlobalTransform::from_translation(translation),
+                    JustPurchased,
+                ))
+                .with_children(|builder| {
+                    info!("Spawning child marker for splitter");
+                    builder.spawn((
+                        SplitterMarker,
+                        MaterialMesh2dBundle {
+                            mesh: stem.into(),
+                            material: black,
+                            transform: Transform::default(),
+                            ..default()
+                        },
+                    ));
+
+                    builder.spawn((
+                        SplitterMarker,
+                        MaterialMesh2dBundle {
+                            mesh: prongs.into(),
+                            material: red,
+                            transform: Transform::default(),
+                            ..default()
+                        },
+                    ));
+                });
+        }
+    }
+}
+
+pub struct SplitterActivate {
+    tile: Entity,
+    position: Position,
+    direction: Direction,
+    vulnerable: Vec<Direction>,
+}
+
+impl Command for SplitterActivate {
+    fn apply(self, world: &mut World) {
+        world.spawn((
+            Splitting::new(self.direction),
+            Consumption::bundle(self.tile, self.vulnerable, self.position),
+        ));
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct SplitterMarker;
@@ -0,0 +1,85 @@
+use bevy::{color::palettes, ecs::world::Command, prelude::*};
+use rand::Rng;
+
+use entropy::EntropyBundle;
+use game_loop::InGame;
+use tiles::{
+    lasers::{Consumption, Position, Scattering},
+    Tile, TileParameters,
+};
+
+// Refracts every laser passing through it in a random direction
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct CrystalTile;
+
+impl CrystalTile {
+    // Deflections rolled per act phase, lasers crossing the crystal more often reuse them
+    pub const ROLLS: usize = 8;
+    // Clockwise steps a laser can be deflected by, never straight through and never back onto
+    // the path it came in on
+    pub const OFFSETS: [u8; 4] = [1, 2, 4, 5];
+}
+
+impl Tile for CrystalTile {
+    const TEXTURE: Option<&'static str> = Some("tiles/crystal.png");
+    fn spawn(position: Position, _player: Entity, game: Entity) -> impl Command {
+        CrystalSpawn { position, game }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        ColorMaterial::from_color(Color::Srgba(palettes::css::ORCHID))
+    }
+
+    fn activate(
+        &self,
+        entity: Entity,
+        parameters: TileParameters,
+        _shooter: Option<Entity>,
+    ) -> impl Command {
+        CrystalActivate {
+            tile: entity,
+            position: parameters.position,
+        }
+    }
+}
+
+pub struct CrystalSpawn {
+    position: Position,
+    game: Entity,
+}
+
+impl Command for CrystalSpawn {
+    fn apply(self, world: &mut World) {
+        world.spawn((CrystalTile, self.position, InGame::new(self.game)));
+    }
+}
+
+pub struct CrystalActivate {
+    tile: Entity,
+    position: Position,
+}
+
+impl Command for CrystalActivate {
+    fn apply(self, world: &mut World) {
+        // Rolled from the game's entropy so that seeded games scatter lasers the same way
+        let offsets = world
+            .get::<InGame>(self.tile)
+            .map(InGame::inner)
+            .and_then(|game| world.get_mut::<EntropyBundle>(game))
+            .map(|mut entropy| {
+                (0..CrystalTile::ROLLS)
+                    .map(|_| {
+                        CrystalTile::OFFSETS
+                            [entropy.entropy.gen_range(0..CrystalTile::OFFSETS.len())]
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        world.spawn((
+            Scattering::new(offsets),
+            Consumption::passing_bundle(self.tile, Vec::new(), self.position),
+        ));
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::{color::palettes, ecs::world::Command, prelude::*};
+
+use game_loop::InGame;
+use tiles::{
+    lasers::{Attenuation, Consumption, Position},
+    Tile, TileParameters,
+};
+
+// Lasers passing through fog come out weaker, down to the base laser power
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct FogTile;
+
+impl FogTile {
+    pub const ATTENUATION: usize = 1;
+}
+
+impl Tile for FogTile {
+    const TEXTURE: Option<&'static str> = Some("tiles/fog.png");
+    fn spawn(position: Position, _player: Entity, game: Entity) -> impl Command {
+        FogSpawn { position, game }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        ColorMaterial::from_color(Color::Srgba(palettes::css::LIGHT_GRAY))
+    }
+
+    fn activate(
+        &self,
+        entity: Entity,
+        parameters: TileParameters,
+        _shooter: Option<Entity>,
+    ) -> impl Command {
+        FogActivate {
+            tile: entity,
+            position: parameters.position,
+        }
+    }
+}
+
+pub struct FogSpawn {
+    position: Position,
+    game: Entity,
+}
+
+impl Command for FogSpawn {
+    fn apply(self, world: &mut World) {
+        world.spawn((FogTile, self.position, InGame::new(self.game)));
+    }
+}
+
+pub struct FogActivate {
+    tile: Entity,
+    position: Position,
+}
+
+impl Command for FogActivate {
+    fn apply(self, world: &mut World) {
+        world.spawn((
+            Attenuation::new(FogTile::ATTENUATION),
+            Consumption::passing_bundle(self.tile, Vec::new(), self.position),
+        ));
+    }
+}
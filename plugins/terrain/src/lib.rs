@@ -0,0 +1,24 @@
+use bevy::prelude::*;
+
+use tiles::TilePlugin;
+
+mod crystal;
+mod fog;
+mod water;
+
+pub use crystal::*;
+pub use fog::*;
+pub use water::*;
+
+// Map features that nobody can build on but that change how lasers travel through them
+pub struct TerrainPlugin;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            TilePlugin::<WaterTile>::default(),
+            TilePlugin::<CrystalTile>::default(),
+            TilePlugin::<FogTile>::default(),
+        ));
+    }
+}
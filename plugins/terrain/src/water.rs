@@ -0,0 +1,61 @@
+use bevy::{color::palettes, ecs::world::Command, prelude::*};
+
+use game_loop::InGame;
+use tiles::{
+    lasers::{Consumption, Position},
+    Tile, TileParameters,
+};
+
+// Blocks building on the hex while lasers pass straight over it
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct WaterTile;
+
+impl Tile for WaterTile {
+    const TEXTURE: Option<&'static str> = Some("tiles/water.png");
+    fn spawn(position: Position, _player: Entity, game: Entity) -> impl Command {
+        WaterSpawn { position, game }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        ColorMaterial::from_color(Color::Srgba(palettes::css::ROYAL_BLUE))
+    }
+
+    fn activate(
+        &self,
+        entity: Entity,
+        parameters: TileParameters,
+        _shooter: Option<Entity>,
+    ) -> impl Command {
+        WaterActivate {
+            tile: entity,
+            position: parameters.position,
+        }
+    }
+}
+
+pub struct WaterSpawn {
+    position: Position,
+    game: Entity,
+}
+
+impl Command for WaterSpawn {
+    fn apply(self, world: &mut World) {
+        world.spawn((WaterTile, self.position, InGame::new(self.game)));
+    }
+}
+
+pub struct WaterActivate {
+    tile: Entity,
+    position: Position,
+}
+
+impl Command for WaterActivate {
+    fn apply(self, world: &mut World) {
+        world.spawn(Consumption::passing_bundle(
+            self.tile,
+            Vec::new(),
+            self.position,
+        ));
+    }
+}
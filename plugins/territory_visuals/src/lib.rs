@@ -0,0 +1,239 @@
+use std::collections::HashSet;
+
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+use hexx::{Hex, HexLayout};
+
+use game_loop::PlayerColor;
+use shop::ControllingPlayer;
+use tilemap::{TileLayer, TilemapLayout, TilemapPlugin};
+use tiles::{Territory, TerritoryChanged};
+
+// Draws every player's territory as a translucent fill with a border along its edge, on top of
+// the tilemap instead of swapping the materials of its tiles
+pub struct TerritoryVisualsPlugin;
+
+impl Plugin for TerritoryVisualsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<TerritoryDisplay>()
+            .init_resource::<TerritoryDisplay>()
+            .add_systems(
+                Update,
+                (Self::rebuild_territories, Self::update_visibility)
+                    .chain()
+                    .in_set(TerritoryVisualsSystems),
+            );
+    }
+}
+
+impl TerritoryVisualsPlugin {
+    pub const FILL_ALPHA: f32 = 0.2;
+    // Fraction of the hex radius covered by the border on the inside of the territory
+    pub const BORDER_WIDTH: f32 = 0.08;
+
+    #[allow(clippy::too_many_arguments)]
+    fn rebuild_territories(
+        mut commands: Commands,
+        mut territory_changes: EventReader<TerritoryChanged>,
+        players: Query<(&Territory, Option<&PlayerColor>)>,
+        tiles: Query<&tilemap::Tile>,
+        tilemaps: Query<&TilemapLayout>,
+        visuals: Query<(Entity, &TerritoryVisual)>,
+        mut meshes: ResMut<Assets<Mesh>>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        let changed = territory_changes
+            .read()
+            .map(|change| change.player)
+            .collect::<HashSet<_>>();
+        if changed.is_empty() {
+            return;
+        }
+        let Ok(layout) = tilemaps.get_single() else {
+            info!("Did not get the single tilemap layout for territories");
+            return;
+        };
+
+        for (entity, _) in visuals
+            .iter()
+            .filter(|(_, visual)| changed.contains(&visual.player))
+        {
+            commands.entity(entity).despawn_recursive();
+        }
+
+        for player in changed {
+            let Ok((territory, color)) = players.get(player) else {
+                continue;
+            };
+            let hexes = territory
+                .iter()
+                .filter_map(|tile| tiles.get(*tile).ok())
+                .map(|tile| **tile)
+                .collect::<HashSet<_>>();
+            if hexes.is_empty() {
+                continue;
+            }
+            let color = color.map_or(Color::WHITE, |color| **color);
+
+            commands.spawn((
+                TerritoryVisual { player },
+                ColorMesh2dBundle {
+                    mesh: meshes
+                        .add(TerritoryVisual::fill_mesh(layout, &hexes))
+                        .into(),
+                    material: materials.add(color.with_alpha(Self::FILL_ALPHA)),
+                    // Tints the tiles below the buildings standing on them
+                    transform: Transform::from_xyz(
+                        0.,
+                        0.,
+                        TilemapPlugin::TILE_Z + TileLayer::Territory.z(),
+                    ),
+                    ..default()
+                },
+            ));
+            commands.spawn((
+                TerritoryVisual { player },
+                ColorMesh2dBundle {
+                    mesh: meshes
+                        .add(TerritoryVisual::border_mesh(layout, &hexes))
+                        .into(),
+                    material: materials.add(color),
+                    // Between the fill and the buildings, so it stays visible wherever the fill is
+                    transform: Transform::from_xyz(
+                        0.,
+                        0.,
+                        TilemapPlugin::TILE_Z
+                            + (TileLayer::Territory.z() + TileLayer::Building.z()) / 2.,
+                    ),
+                    ..default()
+                },
+            ));
+        }
+    }
+
+    fn update_visibility(
+        display: Res<TerritoryDisplay>,
+        controlling_player: Option<Res<ControllingPlayer>>,
+        mut visuals: Query<(&TerritoryVisual, &mut Visibility)>,
+    ) {
+        for (visual, mut visibility) in &mut visuals {
+            let shown = display.all_players
+                || controlling_player
+                    .as_deref()
+                    .is_some_and(|player| **player == visual.player);
+            let target = if shown {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+            if *visibility != target {
+                *visibility = target;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(SystemSet)]
+pub struct TerritoryVisualsSystems;
+
+// Whether the territories of all players are drawn or only the one of the controlling player
+#[derive(Clone, Copy, Debug)]
+#[derive(Resource, Reflect)]
+pub struct TerritoryDisplay {
+    pub all_players: bool,
+}
+
+impl Default for TerritoryDisplay {
+    fn default() -> Self {
+        TerritoryDisplay { all_players: true }
+    }
+}
+
+impl TerritoryDisplay {
+    pub fn toggle(&mut self) {
+        self.all_players = !self.all_players;
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct TerritoryVisual {
+    pub player: Entity,
+}
+
+impl TerritoryVisual {
+    fn fill_mesh(layout: &HexLayout, hexes: &HashSet<Hex>) -> Mesh {
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+
+        for hex in hexes {
+            let center = positions.len() as u32;
+            positions.push(layout.hex_to_world_pos(*hex).extend(0.).to_array());
+            positions.extend(
+                layout
+                    .hex_corners(*hex)
+                    .map(|corner| corner.extend(0.).to_array()),
+            );
+            for corner in 0..6 {
+                indices.extend([center, center + 1 + corner, center + 1 + (corner + 1) % 6]);
+            }
+        }
+
+        Self::mesh(positions, indices)
+    }
+
+    // Every hex edge facing a hex outside the territory gets a strip drawn along its inner side
+    fn border_mesh(layout: &HexLayout, hexes: &HashSet<Hex>) -> Mesh {
+        let mut positions = Vec::new();
+        let mut indices = Vec::new();
+
+        for hex in hexes {
+            let center = layout.hex_to_world_pos(*hex);
+            let corners = layout.hex_corners(*hex);
+
+            for neighbor in hex
+                .all_neighbors()
+                .into_iter()
+                .filter(|neighbor| !hexes.contains(neighbor))
+            {
+                // The shared edge runs between the two corners closest to the neighbor
+                let neighbor_center = layout.hex_to_world_pos(neighbor);
+                let mut edge = corners;
+                edge.sort_by(|a, b| {
+                    a.distance_squared(neighbor_center)
+                        .total_cmp(&b.distance_squared(neighbor_center))
+                });
+                let [start, end, ..] = edge;
+
+                let first = positions.len() as u32;
+                positions.extend(
+                    [
+                        start,
+                        end,
+                        end.lerp(center, TerritoryVisualsPlugin::BORDER_WIDTH),
+                        start.lerp(center, TerritoryVisualsPlugin::BORDER_WIDTH),
+                    ]
+                    .map(|point| point.extend(0.).to_array()),
+                );
+                indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+            }
+        }
+
+        Self::mesh(positions, indices)
+    }
+
+    fn mesh(positions: Vec<[f32; 3]>, indices: Vec<u32>) -> Mesh {
+        Mesh::new(
+            PrimitiveTopology::TriangleList,
+            RenderAssetUsages::RENDER_WORLD,
+        )
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+        .with_inserted_indices(Indices::U32(indices))
+    }
+}
@@ -2,18 +2,21 @@ use std::collections::HashMap;
 use std::f32::consts::PI;
 
 use bevy::color::palettes;
+use bevy::ecs::world::Command;
 use bevy::log::info;
 use bevy::prelude::{
-    resource_added, resource_exists_and_changed, resource_removed, App, Assets, BuildChildren,
-    Bundle, Camera, Color, ColorMaterial, ColorMesh2dBundle, Commands, Component, Deref, DerefMut,
-    DespawnRecursiveExt, Entity, GlobalTransform, Handle, IntoSystemConfigs, Mesh, Name, Plugin,
-    Query, Reflect, Res, ResMut, Resource, SpatialBundle, Startup, SystemSet, Text, Text2dBundle,
-    TextStyle, Transform, Update, Vec3Swizzles, Window, With, Without,
+    resource_added, resource_exists_and_changed, resource_removed, Added, App, Assets,
+    BuildChildren, BuildWorldChildren, Bundle, Camera, Color, ColorMaterial, ColorMesh2dBundle,
+    Commands, Component, Deref, DerefMut, DespawnRecursiveExt, Entity, GlobalTransform, Handle,
+    IntoSystemConfigs, Local, Mesh, Name, Plugin, Query, Reflect, Res, ResMut, Resource,
+    SpatialBundle, Startup, SystemSet, Text, Text2dBundle, TextStyle, Transform, Update,
+    Vec3Swizzles, Window, With, Without, World,
 };
 use bevy::render::{
     mesh::{Indices, PrimitiveTopology},
     render_asset::RenderAssetUsages,
 };
+use bevy::sprite::Mesh2dHandle;
 
 use bevy::window::PrimaryWindow;
 
@@ -23,31 +26,28 @@ pub struct TilemapPlugin;
 
 impl Plugin for TilemapPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Startup,
-            (
-                EmptyTileMaterial::startup_system,
-                TerritoryTileMaterial::startup_system,
-            ),
-        )
-        .add_systems(
-            Update,
-            (
-                Self::destroy_targeted_tile.run_if(resource_removed::<TargetedTile>()),
-                Self::update_targeted_tile.run_if(resource_exists_and_changed::<TargetedTile>),
-                Self::spawn_targeted_tile.run_if(resource_added::<TargetedTile>),
-                Self::handle_cursor_position,
-                Self::update_cursor_directions,
-            )
-                .chain()
-                .in_set(TilemapSystems),
-        );
+        app.add_systems(Startup, EmptyTileMaterial::startup_system)
+            .add_systems(
+                Update,
+                (
+                    Self::destroy_targeted_tile.run_if(resource_removed::<TargetedTile>()),
+                    Self::update_targeted_tile.run_if(resource_exists_and_changed::<TargetedTile>),
+                    Self::spawn_targeted_tile.run_if(resource_added::<TargetedTile>),
+                    Self::handle_cursor_position,
+                    Self::update_cursor_directions,
+                    Self::reset_empty_tiles,
+                )
+                    .chain()
+                    .in_set(TilemapSystems),
+            );
     }
 }
 
 impl TilemapPlugin {
     /// World size of the hexagons (outer radius)
     pub const HEX_SIZE: Vec2 = Vec2::splat(60.0);
+    /// Depth of the tilemap tiles, their layers are drawn just above it
+    pub const TILE_Z: f32 = 10.;
 
     fn handle_cursor_position(
         mut commands: Commands,
@@ -56,6 +56,7 @@ impl TilemapPlugin {
         tilemaps: Query<(Entity, &TilemapLayout, &TilemapEntities)>,
         targeted_tile: Option<ResMut<TargetedTile>>,
         cursor_position: Option<ResMut<CursorWorldPosition>>,
+        mut last_hovered: Local<Option<Hex>>,
     ) {
         let Ok(window) = windows.get_single() else {
             return;
@@ -81,6 +82,11 @@ impl TilemapPlugin {
 
         // convert to hex and back to "snap" to the hex border
         let coord: Hex = layout.world_pos_to_hex(position);
+        // Only retarget when the mouse reaches a new hex, so other controls can move the target
+        if *last_hovered == Some(coord) {
+            return;
+        }
+        *last_hovered = Some(coord);
         if let Some(hovered_tile) = tiles.get(&coord).copied() {
             if let Some(mut targeted_tile) = targeted_tile {
                 targeted_tile.tile = hovered_tile;
@@ -147,6 +153,16 @@ impl TilemapPlugin {
         }
     }
 
+    // Tiles are freed when the building on them is removed, so they need their empty look back
+    fn reset_empty_tiles(
+        mut commands: Commands,
+        tiles: Query<Entity, (With<Tile>, Added<EmptyTile>)>,
+    ) {
+        for tile in &tiles {
+            commands.add(SetTileLayer::clear(tile, TileLayer::Building));
+        }
+    }
+
     fn spawn_targeted_tile(
         mut commands: Commands,
         tilemaps: Query<(Entity, &TilemapLayout), With<Tilemap>>,
@@ -260,25 +276,139 @@ pub struct TilemapEntities {
 #[derive(Component)]
 pub struct EmptyTile;
 
-#[derive(Deref, Resource)]
-pub struct EmptyTileMaterial(Handle<ColorMaterial>);
+// Layers drawn over a tilemap tile from the bottom up, the tile's own mesh is the base terrain and
+// every other layer is a child mesh so that each can be colored without touching the others
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Reflect)]
+pub enum TileLayer {
+    Base,
+    Territory,
+    Building,
+    Preview,
+    Selection,
+}
 
-impl EmptyTileMaterial {
-    fn new(materials: &mut Assets<ColorMaterial>) -> Self {
-        EmptyTileMaterial(materials.add(Color::WHITE))
+impl TileLayer {
+    pub const ALL: [Self; 5] = [
+        Self::Base,
+        Self::Territory,
+        Self::Building,
+        Self::Preview,
+        Self::Selection,
+    ];
+
+    // Depth of the layer relative to its tile
+    pub fn z(&self) -> f32 {
+        *self as u8 as f32 * 0.1
     }
+}
 
-    fn startup_system(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
-        commands.insert_resource(Self::new(materials.as_mut()));
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+pub struct TileLayerMesh {
+    pub layer: TileLayer,
+}
+
+// Child layer meshes of a tilemap tile
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Deref, DerefMut)]
+pub struct TileLayers(HashMap<TileLayer, Entity>);
+
+// Colors one layer of a tilemap tile, or clears it when no material is given
+pub struct SetTileLayer {
+    tile: Entity,
+    layer: TileLayer,
+    material: Option<Handle<ColorMaterial>>,
+}
+
+impl SetTileLayer {
+    pub fn new(tile: Entity, layer: TileLayer, material: Handle<ColorMaterial>) -> Self {
+        SetTileLayer {
+            tile,
+            layer,
+            material: Some(material),
+        }
+    }
+
+    pub fn clear(tile: Entity, layer: TileLayer) -> Self {
+        SetTileLayer {
+            tile,
+            layer,
+            material: None,
+        }
+    }
+}
+
+impl Command for SetTileLayer {
+    fn apply(self, world: &mut World) {
+        if world.get_entity(self.tile).is_none() {
+            return;
+        }
+        // The base layer is the tile itself
+        if let TileLayer::Base = self.layer {
+            if let (Some(material), Some(mut handle)) = (
+                self.material,
+                world.get_mut::<Handle<ColorMaterial>>(self.tile),
+            ) {
+                *handle = material;
+            }
+            return;
+        }
+
+        let existing = world
+            .get::<TileLayers>(self.tile)
+            .and_then(|layers| layers.get(&self.layer).copied());
+
+        match (existing, self.material) {
+            (Some(layer), Some(material)) => {
+                if let Some(mut handle) = world.get_mut::<Handle<ColorMaterial>>(layer) {
+                    *handle = material;
+                }
+            }
+            (Some(layer), None) => {
+                if let Some(mut layers) = world.get_mut::<TileLayers>(self.tile) {
+                    layers.remove(&self.layer);
+                }
+                if let Some(layer) = world.get_entity_mut(layer) {
+                    layer.despawn_recursive();
+                }
+            }
+            (None, Some(material)) => {
+                let Some(mesh) = world.get::<Mesh2dHandle>(self.tile).cloned() else {
+                    return;
+                };
+                let layer = world
+                    .spawn((
+                        TileLayerMesh { layer: self.layer },
+                        ColorMesh2dBundle {
+                            mesh,
+                            material,
+                            transform: Transform::from_xyz(0., 0., self.layer.z()),
+                            ..Default::default()
+                        },
+                    ))
+                    .id();
+                let Some(mut tile) = world.get_entity_mut(self.tile) else {
+                    return;
+                };
+                tile.add_child(layer);
+                if let Some(mut layers) = tile.get_mut::<TileLayers>() {
+                    layers.insert(self.layer, layer);
+                } else {
+                    tile.insert(TileLayers(HashMap::from([(self.layer, layer)])));
+                }
+            }
+            (None, None) => {}
+        }
     }
 }
 
 #[derive(Deref, Resource)]
-pub struct TerritoryTileMaterial(Handle<ColorMaterial>);
+pub struct EmptyTileMaterial(Handle<ColorMaterial>);
 
-impl TerritoryTileMaterial {
-    fn new(materials: &mut Assets<ColorMaterial>) -> TerritoryTileMaterial {
-        Self(materials.add(Color::from(bevy::color::palettes::basic::SILVER)))
+impl EmptyTileMaterial {
+    fn new(materials: &mut Assets<ColorMaterial>) -> Self {
+        EmptyTileMaterial(materials.add(Color::WHITE))
     }
 
     fn startup_system(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
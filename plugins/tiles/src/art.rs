@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoadFailedEvent, LoadState},
+    color::{Color, ColorToComponents, LinearRgba, Mix},
+    prelude::{
+        AssetId, AssetServer, Assets, Bundle, ColorMaterial, Component, Deref, DerefMut,
+        EventReader, Handle, Image, Query, Res, ResMut, Resource, Sprite, SpriteBundle, Vec2,
+    },
+    sprite::Anchor,
+};
+
+use crate::Tile;
+
+// Flat colors of every tile texture, used in place of the texture when it fails to load
+#[derive(Clone, Debug, Default)]
+#[derive(Resource, Deref, DerefMut)]
+pub struct TileArt(HashMap<AssetId<Image>, Color>);
+
+// Material for a tile darkened by the owner's `PlayerColorAdjuster`, textured when the tile
+// declares art and flat colored when it does not
+pub fn tile_material<T: Tile>(asset_server: &AssetServer, tint: f32) -> ColorMaterial {
+    let texture = T::TEXTURE
+        .map(|path| asset_server.load::<Image>(path))
+        .filter(|texture| !matches!(asset_server.load_state(texture.id()), LoadState::Failed(_)));
+    let color = match texture {
+        Some(_) => Color::WHITE,
+        None => T::material(asset_server).color,
+    };
+    ColorMaterial {
+        color: color.mix(&Color::BLACK, tint),
+        texture,
+    }
+}
+
+// Marker sprites fall back to a flat rectangle in this color when their art is missing
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct SpriteArt {
+    pub fallback: Color,
+}
+
+pub fn marker_sprite(
+    asset_server: &AssetServer,
+    path: &'static str,
+    tint: Color,
+    fallback: Color,
+    size: Vec2,
+    anchor: Anchor,
+) -> impl Bundle {
+    (
+        SpriteArt { fallback },
+        SpriteBundle {
+            texture: asset_server.load(path),
+            sprite: Sprite {
+                color: tint,
+                custom_size: Some(size),
+                anchor,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+}
+
+// Swaps textures that could not be loaded for the flat colors of their tiles, keeping the player
+// tint and transparency that were multiplied into the material
+pub(crate) fn fall_back_to_flat_colors(
+    mut failures: EventReader<AssetLoadFailedEvent<Image>>,
+    art: Res<TileArt>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut sprites: Query<(&SpriteArt, &mut Handle<Image>, &mut Sprite)>,
+) {
+    for failure in failures.read() {
+        for (_, material) in materials.iter_mut() {
+            if material
+                .texture
+                .as_ref()
+                .is_some_and(|texture| texture.id() == failure.id)
+            {
+                let fallback = art.get(&failure.id).copied().unwrap_or(Color::WHITE);
+                let tint = material.color.to_linear().to_vec4();
+                let flat = fallback.to_linear().to_vec4();
+                material.color = LinearRgba::from_vec4(flat * tint).into();
+                material.texture = None;
+            }
+        }
+
+        for (sprite_art, mut texture, mut sprite) in &mut sprites {
+            if texture.id() == failure.id {
+                *texture = Handle::default();
+                sprite.color = sprite_art.fallback;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use bevy::{
+        asset::{io::AssetReaderError, AssetLoadError, AssetLoadFailedEvent},
+        color::{palettes, Color},
+        ecs::system::RunSystemOnce,
+        prelude::{Assets, ColorMaterial, Events, Handle, Image, Sprite, World},
+    };
+
+    use super::{fall_back_to_flat_colors, SpriteArt, TileArt};
+
+    #[test]
+    fn test_failed_texture_falls_back_to_flat_color() {
+        let texture = Handle::<Image>::weak_from_u128(1);
+        let flat = Color::Srgba(palettes::css::CRIMSON);
+        let mut world = World::new();
+        world.init_resource::<Events<AssetLoadFailedEvent<Image>>>();
+        let mut art = TileArt::default();
+        art.insert(texture.id(), flat);
+        world.insert_resource(art);
+        let mut materials = Assets::<ColorMaterial>::default();
+        let material = materials.add(ColorMaterial {
+            color: Color::WHITE,
+            texture: Some(texture.clone()),
+        });
+        world.insert_resource(materials);
+        let sprite = world
+            .spawn((
+                SpriteArt {
+                    fallback: Color::BLACK,
+                },
+                texture.clone(),
+                Sprite::default(),
+            ))
+            .id();
+
+        world.send_event(AssetLoadFailedEvent::<Image> {
+            id: texture.id(),
+            path: "tiles/missing.png".into(),
+            error: AssetLoadError::AssetReaderError(AssetReaderError::NotFound(PathBuf::from(
+                "tiles/missing.png",
+            ))),
+        });
+        world.run_system_once(fall_back_to_flat_colors);
+
+        let material = world
+            .resource::<Assets<ColorMaterial>>()
+            .get(&material)
+            .unwrap();
+        assert!(material.texture.is_none());
+        assert_eq!(material.color.to_linear(), flat.to_linear());
+        let sprite = world.entity(sprite);
+        assert_ne!(sprite.get::<Handle<Image>>().unwrap().id(), texture.id());
+        assert_eq!(sprite.get::<Sprite>().unwrap().color, Color::BLACK);
+    }
+}
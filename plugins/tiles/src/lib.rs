@@ -1,23 +1,57 @@
-use std::{any::TypeId, collections::HashSet, marker::PhantomData};
+use std::{
+    any::TypeId,
+    collections::{HashMap, HashSet},
+    marker::PhantomData,
+};
 
 use bevy::{
-    color::{Color, Mix},
     ecs::world::Command,
     prelude::{
         info, Added, App, AssetServer, Assets, Changed, ColorMaterial, Commands, Component, Deref,
-        DerefMut, Entity, Event, EventReader, Handle, IntoSystemConfigs, IntoSystemSetConfigs, Or,
-        Plugin, Query, Res, ResMut, SystemSet, Update, With, World,
+        DerefMut, Entity, Event, EventReader, Has, Image, IntoSystemConfigs, IntoSystemSetConfigs,
+        Plugin, Query, Reflect, RemovedComponents, Res, ResMut, Resource, Startup, SystemSet,
+        Update, With, Without, World,
     },
 };
 
-use game_loop::{GameLoopSystems, GamePhase, InGame, Player, PlayerColorAdjuster};
+use game_loop::{GameInstance, GameLoopSystems, GamePhase, InGame, Player, PlayerColorAdjuster};
+use health::Health;
+use hexx::Hex;
 pub use lasers;
 use lasers::{
-    Amplification, Direction, LaserHitEvent, LaserPlugin, LaserSystems, Position, Rotation,
+    Amplification, Armor, Consumption, Direction, LaserHitEvent, LaserKind, LaserPlugin,
+    LaserSystems, Position, Rotation, YReflection,
 };
-use tilemap::{EmptyTile, EmptyTileMaterial, Tilemap, TilemapEntities};
+use popups::PopupEvent;
+use tilemap::{EmptyTile, SetTileLayer, TileLayer, Tilemap, TilemapEntities};
+
+mod art;
+mod territory;
+pub use art::*;
+pub use territory::*;
 
 pub trait Tile {
+    // Stackable tiles can be placed on a hex that is already occupied by a non-stackable tile,
+    // e.g. an overlay on an existing tower
+    const STACKABLE: bool = false;
+
+    // Tiles with a max health lose health when hit on a vulnerable face and are destroyed at zero
+    const MAX_HEALTH: Option<usize> = None;
+
+    // Damage from the owner's own lasers is ignored, so towers that relay beams only wear down
+    // from the beams of other players
+    const OWNER_IMMUNE: bool = false;
+
+    // Territory spread around the tile once it has an owner
+    const INFLUENCE: Influence = Influence::new(Territory::RANGE as u32, 1);
+
+    // Map features like mountains stop territory from spreading through them
+    const BLOCKS_TERRITORY: bool = false;
+
+    // Texture drawn for the tile, tinted per player, `material` stays the flat color used when
+    // the tile has no art or it fails to load
+    const TEXTURE: Option<&'static str> = None;
+
     #[allow(unused_variables)]
     fn spawn(position: Position, player: Entity, game: Entity) -> impl Command;
 
@@ -30,8 +64,35 @@ pub trait Tile {
         shooter: Option<Entity>,
     ) -> impl Command;
 
+    // Faces through which lasers hit the tile, used by `activate` to build its `Consumption`
+    #[allow(unused_variables)]
+    fn vulnerable(parameters: &TileParameters) -> Vec<Direction> {
+        Vec::new()
+    }
+
+    // Armored faces absorb, deflect or weaken lasers before they reach the vulnerable faces,
+    // usually declared relative to the tile's `Direction`
     #[allow(unused_variables)]
-    fn on_hit(&self, entity: Entity, strength: usize, shooter: Entity) -> Option<impl Command> {
+    fn armor(parameters: &TileParameters) -> Armor {
+        Armor::default()
+    }
+
+    // Hits from these kinds of lasers are ignored entirely
+    #[allow(unused_variables)]
+    fn immune(kind: LaserKind) -> bool {
+        false
+    }
+
+    // Tiles with a max health take `DamageTile` unless they handle hits themselves, disruptor
+    // hits never reach this and disable the tile instead
+    #[allow(unused_variables)]
+    fn on_hit(
+        &self,
+        entity: Entity,
+        strength: usize,
+        shooter: Entity,
+        kind: LaserKind,
+    ) -> Option<impl Command> {
         None as Option<fn(&mut World)>
     }
 }
@@ -50,61 +111,49 @@ impl Plugin for TilesPlugin {
                 )
                     .chain(),
             )
-            .add_systems(Update, Self::update_territories);
+            .add_event::<TerritoryChanged>()
+            .init_resource::<StackableTiles>()
+            .init_resource::<TileArt>()
+            .add_systems(
+                Update,
+                (
+                    fall_back_to_flat_colors,
+                    Self::add_occupancy,
+                    Self::add_territory_maps,
+                    Self::add_territories,
+                    update_territories.after(TileSystems::Spawn),
+                ),
+            );
     }
 }
 
 impl TilesPlugin {
-    // This could be better optimized in the future by only running conditional on a tile spawning or
-    // being destroyed under a player's control and using a `Changed` query filter to track that
-    // Owned tiles would need to be tracked by a marker component on the player entity or possibly
-    // as children under the entity to implement this
-    fn update_territories(
+    fn add_occupancy(
         mut commands: Commands,
-        tilemaps: Query<&TilemapEntities>,
-        tiles: Query<(&Position, &Owner)>,
-        mut territories: Query<(Entity, Option<&mut Territory>), With<Player>>,
+        games: Query<Entity, (With<GameInstance>, Without<TileOccupancy>)>,
     ) {
-        let Ok(tilemap) = tilemaps.get_single() else {
-            info!("Found none or multiple tilemaps");
-            return;
-        };
-        let mut sorted_tiles = tiles.iter().sort::<&Owner>().peekable();
-
-        for (player, territory) in &mut territories.iter_mut().sort::<Entity>() {
-            let mut updated_territory: HashSet<Entity> = HashSet::new();
-
-            let Some((position, _)) = sorted_tiles.find(|(_, owner)| ***owner == player) else {
-                info!(
-                    "Could not find the relevant owned tiles for player with entity {}",
-                    player
-                );
-                continue;
-            };
-
-            for coord in (**position).range(Territory::RANGE as u32) {
-                if let Some(tile_entity) = tilemap.tiles.get(&coord) {
-                    updated_territory.insert(*tile_entity);
-                }
-            }
+        for game in &games {
+            commands.entity(game).insert(TileOccupancy::default());
+        }
+    }
 
-            while sorted_tiles
-                .peek()
-                .is_some_and(|(_, owner)| ***owner == player)
-            {
-                let (position, _) = sorted_tiles.next().unwrap();
-                for coord in (**position).range(Territory::RANGE as u32) {
-                    if let Some(tile_entity) = tilemap.tiles.get(&coord) {
-                        updated_territory.insert(*tile_entity);
-                    }
-                }
-            }
+    // Players start out with an empty territory, so the first claimed tiles update an existing
+    // component that every system can already see
+    fn add_territories(
+        mut commands: Commands,
+        players: Query<Entity, (With<Player>, Without<Territory>)>,
+    ) {
+        for player in &players {
+            commands.entity(player).insert(Territory::default());
+        }
+    }
 
-            if let Some(mut territory) = territory {
-                **territory = updated_territory;
-            } else {
-                commands.entity(player).insert(Territory(updated_territory));
-            }
+    fn add_territory_maps(
+        mut commands: Commands,
+        games: Query<Entity, (With<GameInstance>, Without<TerritoryMap>)>,
+    ) {
+        for game in &games {
+            commands.entity(game).insert(TerritoryMap::default());
         }
     }
 }
@@ -126,15 +175,29 @@ where
     T: Tile + Component + Send + Sync + 'static,
 {
     fn build(&self, app: &mut App) {
-        app.add_event::<TileSpawnEvent>().add_systems(
-            Update,
-            (
-                Self::spawn_tiles.in_set(TileSystems::Spawn),
-                Self::activate_tiles.in_set(TileSystems::Activate),
-                Self::handle_hit_tiles.in_set(TileSystems::OnHit),
-                Self::update_tile_material,
-            ),
-        );
+        if T::STACKABLE {
+            app.world_mut()
+                .get_resource_or_insert_with(StackableTiles::default)
+                .insert(TypeId::of::<T>());
+        }
+        app.add_event::<TileSpawnEvent>()
+            .add_systems(Startup, Self::register_art)
+            .add_systems(
+                Update,
+                (
+                    Self::spawn_tiles.in_set(TileSystems::Spawn),
+                    (
+                        Self::track_occupants,
+                        Self::untrack_occupants,
+                        Self::insert_health,
+                        Self::insert_influence,
+                    )
+                        .after(TileSystems::Spawn),
+                    Self::activate_tiles.in_set(TileSystems::Activate),
+                    Self::handle_hit_tiles.in_set(TileSystems::OnHit),
+                    Self::update_tile_material,
+                ),
+            );
     }
 }
 
@@ -150,9 +213,12 @@ where
             &Position,
             Option<&Direction>,
             Option<&Rotation>,
+            Option<&YReflection>,
             Option<&Amplification>,
+            Option<&Level>,
             Option<&Owner>,
             &T,
+            Has<Disrupted>,
             &InGame,
         )>,
     ) {
@@ -163,8 +229,19 @@ where
                 continue;
             }
 
-            let Some((entity, position, direction, rotation, amplification, owner, tile, _)) =
-                sorted_tiles.find(|(_, _, _, _, _, _, _, in_game)| ***in_game == game)
+            let Some((
+                entity,
+                position,
+                direction,
+                rotation,
+                tilt,
+                amplification,
+                level,
+                owner,
+                tile,
+                disrupted,
+                _,
+            )) = sorted_tiles.find(|(.., in_game)| ***in_game == game)
             else {
                 info!(
                     "failed to find tiles for game {:?}! None exist or invalid sort(?)",
@@ -172,41 +249,85 @@ where
                 );
                 continue;
             };
-            let parameters = TileParameters::new(position, direction, rotation, amplification);
-            commands.add(tile.activate(entity, parameters, owner.and_then(|owner| Some(owner.0))));
+            let parameters =
+                TileParameters::new(position, direction, rotation, tilt, amplification, level);
+            Self::activate_tile(&mut commands, entity, tile, parameters, owner, disrupted);
 
             while sorted_tiles
                 .peek()
-                .is_some_and(|(_, _, _, _, _, _, _, in_game)| ***in_game == game)
+                .is_some_and(|(.., in_game)| ***in_game == game)
             {
-                let (entity, position, direction, rotation, amplification, owner, tile, _) =
-                    sorted_tiles.next().unwrap();
-
-                let parameters = TileParameters::new(position, direction, rotation, amplification);
-                commands.add(tile.activate(
+                let (
                     entity,
-                    parameters,
-                    owner.and_then(|owner| Some(owner.0)),
-                ));
+                    position,
+                    direction,
+                    rotation,
+                    tilt,
+                    amplification,
+                    level,
+                    owner,
+                    tile,
+                    disrupted,
+                    _,
+                ) = sorted_tiles.next().unwrap();
+
+                let parameters =
+                    TileParameters::new(position, direction, rotation, tilt, amplification, level);
+                Self::activate_tile(&mut commands, entity, tile, parameters, owner, disrupted);
             }
         }
     }
 
+    // Disrupted tiles sit out a single act phase, they still block lasers but have no effect
+    fn activate_tile(
+        commands: &mut Commands,
+        entity: Entity,
+        tile: &T,
+        parameters: TileParameters,
+        owner: Option<&Owner>,
+        disrupted: bool,
+    ) {
+        if disrupted {
+            info!("Tile {:?} is disrupted and skips activation", entity);
+            commands.entity(entity).remove::<Disrupted>();
+            commands.add(DisruptedActivate {
+                tile: entity,
+                position: parameters.position,
+                vulnerable: T::vulnerable(&parameters),
+            });
+        } else {
+            commands.add(tile.activate(entity, parameters, owner.and_then(|owner| Some(owner.0))));
+        }
+    }
+
     fn handle_hit_tiles(
         mut commands: Commands,
         mut collisions: EventReader<LaserHitEvent>,
-        tiles: Query<(Entity, &Position, &T)>,
+        tiles: Query<(Entity, &Position, &T, Option<&Owner>)>,
     ) {
         for LaserHitEvent {
             strength,
             consumer,
             shooter,
+            kind,
         } in collisions.read()
         {
-            if let Ok((entity, _position, tile)) = tiles.get(*consumer) {
-                if let Some(command) = tile.on_hit(entity, *strength, *shooter) {
+            if let Ok((entity, _position, tile, owner)) = tiles.get(*consumer) {
+                if T::immune(*kind) {
+                    continue;
+                }
+                if matches!(kind, LaserKind::Disruptor) {
+                    commands.add(DisruptTile { tile: entity });
+                } else if let Some(command) = tile.on_hit(entity, *strength, *shooter, *kind) {
                     info!("Tile hit command added to queue");
                     commands.add(command);
+                } else if T::MAX_HEALTH.is_some()
+                    && !(T::OWNER_IMMUNE && owner.is_some_and(|owner| **owner == *shooter))
+                {
+                    commands.add(DamageTile {
+                        tile: entity,
+                        strength: *strength,
+                    });
                 }
             }
         }
@@ -216,6 +337,8 @@ where
         mut commands: Commands,
         mut tile_spawns: EventReader<TileSpawnEvent>,
         tilemaps: Query<&TilemapEntities, With<Tilemap>>,
+        occupancies: Query<&TileOccupancy>,
+        stackable: Res<StackableTiles>,
     ) {
         let tile_spawns = tile_spawns
             .read()
@@ -232,6 +355,12 @@ where
             for tile_spawn in &tile_spawns {
                 for (hex, tile_entity) in &tilemap_entities.tiles {
                     if *tile_entity == tile_spawn.on_tile {
+                        if occupancies.get(tile_spawn.game).is_ok_and(|occupancy| {
+                            !occupancy.can_place(hex, TypeId::of::<T>(), &stackable)
+                        }) {
+                            info!("Skipping tile spawn on occupied hex {:?}", hex);
+                            continue;
+                        }
                         commands.add(T::spawn(
                             Position::from(*hex),
                             tile_spawn.owner,
@@ -245,46 +374,159 @@ where
         }
     }
 
+    fn register_art(asset_server: Res<AssetServer>, mut art: ResMut<TileArt>) {
+        if let Some(path) = T::TEXTURE {
+            art.insert(
+                asset_server.load::<Image>(path).id(),
+                T::material(&asset_server).color,
+            );
+        }
+    }
+
+    fn insert_health(mut commands: Commands, tiles: Query<Entity, (Added<T>, Without<Health>)>) {
+        let Some(max_health) = T::MAX_HEALTH else {
+            return;
+        };
+        for tile in &tiles {
+            commands.entity(tile).insert(Health::new(max_health));
+        }
+    }
+
+    fn insert_influence(mut commands: Commands, tiles: Query<Entity, Added<T>>) {
+        for tile in &tiles {
+            commands.entity(tile).insert(T::INFLUENCE);
+            if T::BLOCKS_TERRITORY {
+                commands.entity(tile).insert(TerritoryBlocker);
+            }
+        }
+    }
+
+    fn track_occupants(
+        tiles: Query<(Entity, &Position, &InGame), Added<T>>,
+        mut occupancies: Query<&mut TileOccupancy>,
+    ) {
+        for (entity, position, in_game) in &tiles {
+            if let Ok(mut occupancy) = occupancies.get_mut(**in_game) {
+                occupancy
+                    .entry(**position)
+                    .or_default()
+                    .push((entity, TypeId::of::<T>()));
+            }
+        }
+    }
+
+    fn untrack_occupants(
+        mut removed: RemovedComponents<T>,
+        mut occupancies: Query<&mut TileOccupancy>,
+    ) {
+        for entity in removed.read() {
+            for mut occupancy in &mut occupancies {
+                occupancy.remove_occupant(entity);
+            }
+        }
+    }
+
+    // Buildings and map features color the building layer of the hex they stand on
     fn update_tile_material(
-        added_tiles: Query<
-            (&Position, Option<&Owner>, Option<&T>, Option<&EmptyTile>),
-            Or<(Added<T>, Added<EmptyTile>)>,
-        >,
+        mut commands: Commands,
+        added_tiles: Query<(&Position, Option<&Owner>), Added<T>>,
         tilemaps: Query<&TilemapEntities, With<Tilemap>>,
-        mut materials: Query<&mut Handle<ColorMaterial>>,
         mut material_assets: ResMut<Assets<ColorMaterial>>,
         players: Query<&PlayerColorAdjuster>,
         asset_server: Res<AssetServer>,
-        empty_tile_material: Res<EmptyTileMaterial>,
     ) {
         let Ok(tiles) = tilemaps.get_single() else {
             return;
         };
 
-        for (position, owner, tile, empty) in &added_tiles {
-            let hex = **position;
-            if let Some(mut material) = tiles
-                .get(&hex)
-                .and_then(|&entity| materials.get_mut(entity).ok())
-            {
-                let darkening = match owner.and_then(|owner| players.get(**owner).ok()) {
-                    Some(value) => value,
-                    None => &PlayerColorAdjuster(0.),
-                };
-
-                if let Some(_) = tile {
-                    *material = material_assets.add(
-                        T::material(&asset_server)
-                            .color
-                            .mix(&Color::BLACK, **darkening),
-                    );
-                }
+        for (position, owner) in &added_tiles {
+            let Some(tile_entity) = tiles.get(&**position) else {
+                continue;
+            };
+            let darkening = match owner.and_then(|owner| players.get(**owner).ok()) {
+                Some(value) => value,
+                None => &PlayerColorAdjuster(0.),
+            };
 
-                if let Some(_) = empty {
-                    info!("Change to empty tile material");
-                    *material = empty_tile_material.clone_weak();
-                }
-            }
+            commands.add(SetTileLayer::new(
+                *tile_entity,
+                TileLayer::Building,
+                material_assets.add(tile_material::<T>(&asset_server, **darkening)),
+            ));
+        }
+    }
+}
+
+// Marks a tile hit by a disruptor laser, it is not activated in the next act phase
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+pub struct Disrupted;
+
+pub struct DisruptTile {
+    pub tile: Entity,
+}
+
+impl Command for DisruptTile {
+    fn apply(self, world: &mut World) {
+        let Some(mut tile) = world.get_entity_mut(self.tile) else {
+            return;
+        };
+        tile.insert(Disrupted);
+        let Some(position) = tile.get::<Position>().copied() else {
+            return;
+        };
+
+        if let Some(tile_entity) = position.get_tile_entity(world) {
+            world.trigger_targets(
+                PopupEvent {
+                    text: "Disrupted".to_string(),
+                },
+                tile_entity,
+            );
+        }
+    }
+}
+
+// Activation of a disrupted tile, which only absorbs lasers on its vulnerable faces
+struct DisruptedActivate {
+    tile: Entity,
+    position: Position,
+    vulnerable: Vec<Direction>,
+}
+
+impl Command for DisruptedActivate {
+    fn apply(self, world: &mut World) {
+        world.spawn(Consumption::bundle(
+            self.tile,
+            self.vulnerable,
+            self.position,
+        ));
+    }
+}
+
+// Damage dealt to a tile by a laser, shown as a popup over the tile
+pub struct DamageTile {
+    pub tile: Entity,
+    pub strength: usize,
+}
+
+impl Command for DamageTile {
+    fn apply(self, world: &mut World) {
+        let Some(mut health) = world.get_mut::<Health>(self.tile) else {
+            return;
+        };
+        **health = health.saturating_sub(self.strength);
+        let Some(position) = world.get::<Position>(self.tile).copied() else {
+            return;
+        };
+
+        if let Some(tile_entity) = position.get_tile_entity(world) {
+            world.trigger_targets(
+                PopupEvent {
+                    text: format!("-{}", self.strength),
+                },
+                tile_entity,
+            );
         }
     }
 }
@@ -321,7 +563,9 @@ pub struct TileParameters {
     pub position: Position,
     pub direction: Option<Direction>,
     pub rotation: Option<Rotation>,
+    pub tilt: Option<YReflection>,
     pub amplification: Option<Amplification>,
+    pub level: Level,
 }
 
 impl TileParameters {
@@ -329,7 +573,9 @@ impl TileParameters {
         position: &Position,
         direction: Option<&Direction>,
         rotation: Option<&Rotation>,
+        tilt: Option<&YReflection>,
         amplification: Option<&Amplification>,
+        level: Option<&Level>,
     ) -> TileParameters {
         let direction = direction.and_then(|direction| Some(*direction));
         let rotation = rotation.and_then(|rotation| Some(*rotation));
@@ -338,7 +584,9 @@ impl TileParameters {
             position: *position,
             direction,
             rotation,
+            tilt: tilt.copied(),
             amplification,
+            level: level.copied().unwrap_or_default(),
         }
     }
     pub fn from_position(position: &Position) -> TileParameters {
@@ -349,10 +597,215 @@ impl TileParameters {
     }
 }
 
-#[derive(Clone, Debug)]
+// Upgrade level of a purchased tile, tiles without one are at the base level
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Component, Deref, Reflect)]
+pub struct Level(u8);
+
+impl Level {
+    pub const BASE: Level = Level(1);
+    pub const MAX: Level = Level(3);
+
+    pub fn new(level: u8) -> Level {
+        Self(level)
+    }
+
+    pub fn next(&self) -> Option<Level> {
+        (*self < Self::MAX).then_some(Level(self.0 + 1))
+    }
+}
+
+impl Default for Level {
+    fn default() -> Self {
+        Self::BASE
+    }
+}
+
+impl std::fmt::Display for Level {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Tiles standing on each hex of a game along with their tile type, kept on the game entity
+#[derive(Clone, Debug, Default)]
 #[derive(Component, Deref, DerefMut)]
-pub struct Territory(HashSet<Entity>);
+pub struct TileOccupancy(HashMap<Hex, Vec<(Entity, TypeId)>>);
+
+impl TileOccupancy {
+    pub fn occupants(&self, hex: &Hex) -> impl Iterator<Item = Entity> + '_ {
+        self.get(hex)
+            .into_iter()
+            .flat_map(|occupants| occupants.iter().map(|(entity, _)| *entity))
+    }
+
+    pub fn is_occupied(&self, hex: &Hex) -> bool {
+        self.get(hex).is_some_and(|occupants| !occupants.is_empty())
+    }
+
+    // Empty hexes take any tile, occupied hexes only take a stackable tile and only if no other
+    // stackable tile is already there
+    pub fn can_place(&self, hex: &Hex, tile_id: TypeId, stackable: &StackableTiles) -> bool {
+        let Some(occupants) = self.get(hex).filter(|occupants| !occupants.is_empty()) else {
+            return true;
+        };
+        stackable.contains(&tile_id)
+            && occupants
+                .iter()
+                .all(|(_, occupant)| !stackable.contains(occupant))
+    }
+
+    pub fn remove_occupant(&mut self, entity: Entity) {
+        self.retain(|_, occupants| {
+            occupants.retain(|(occupant, _)| *occupant != entity);
+            !occupants.is_empty()
+        });
+    }
+}
+
+// Tile types that declared themselves `Tile::STACKABLE`
+#[derive(Clone, Debug, Default)]
+#[derive(Deref, DerefMut, Resource)]
+pub struct StackableTiles(HashSet<TypeId>);
+
+#[cfg(test)]
+mod tests {
+    use std::any::TypeId;
+
+    use bevy::{
+        ecs::{system::RunSystemOnce, world::Command},
+        prelude::{App, AssetServer, ColorMaterial, Component, Entity, Events, World},
+    };
+    use health::Health;
+    use hexx::Hex;
+
+    use super::{
+        lasers::{LaserHitEvent, LaserKind, Position},
+        Owner, StackableTiles, Tile, TileOccupancy, TileParameters, TilePlugin,
+    };
+
+    #[derive(Component)]
+    struct TestTower;
+
+    impl Tile for TestTower {
+        fn spawn(_position: Position, _player: Entity, _game: Entity) -> impl Command {
+            |_: &mut World| {}
+        }
+
+        fn material(_asset_server: &AssetServer) -> ColorMaterial {
+            ColorMaterial::default()
+        }
+
+        fn activate(
+            &self,
+            _entity: Entity,
+            _parameters: TileParameters,
+            _shooter: Option<Entity>,
+        ) -> impl Command {
+            |_: &mut World| {}
+        }
+    }
+
+    #[derive(Component)]
+    struct TestOverlay;
+
+    impl Tile for TestOverlay {
+        const STACKABLE: bool = true;
 
-impl Territory {
-    pub const RANGE: usize = 4;
+        fn spawn(_position: Position, _player: Entity, _game: Entity) -> impl Command {
+            |_: &mut World| {}
+        }
+
+        fn material(_asset_server: &AssetServer) -> ColorMaterial {
+            ColorMaterial::default()
+        }
+
+        fn activate(
+            &self,
+            _entity: Entity,
+            _parameters: TileParameters,
+            _shooter: Option<Entity>,
+        ) -> impl Command {
+            |_: &mut World| {}
+        }
+    }
+
+    #[test]
+    fn test_stackable_tile_placement() {
+        let mut app = App::new();
+        app.add_plugins((
+            TilePlugin::<TestTower>::default(),
+            TilePlugin::<TestOverlay>::default(),
+        ));
+        let stackable = app.world().resource::<StackableTiles>();
+        assert!(stackable.contains(&TypeId::of::<TestOverlay>()));
+        assert!(!stackable.contains(&TypeId::of::<TestTower>()));
+
+        let hex = Hex::ORIGIN;
+        let mut occupancy = TileOccupancy::default();
+        assert!(occupancy.can_place(&hex, TypeId::of::<TestTower>(), stackable));
+
+        occupancy.insert(hex, vec![(Entity::from_raw(1), TypeId::of::<TestTower>())]);
+        assert!(occupancy.can_place(&hex, TypeId::of::<TestOverlay>(), stackable));
+        assert!(!occupancy.can_place(&hex, TypeId::of::<TestTower>(), stackable));
+
+        occupancy
+            .entry(hex)
+            .or_default()
+            .push((Entity::from_raw(2), TypeId::of::<TestOverlay>()));
+        assert!(!occupancy.can_place(&hex, TypeId::of::<TestOverlay>(), stackable));
+    }
+
+    #[derive(Component)]
+    struct TestRelay;
+
+    impl Tile for TestRelay {
+        const MAX_HEALTH: Option<usize> = Some(2);
+        const OWNER_IMMUNE: bool = true;
+
+        fn spawn(_position: Position, _player: Entity, _game: Entity) -> impl Command {
+            |_: &mut World| {}
+        }
+
+        fn material(_asset_server: &AssetServer) -> ColorMaterial {
+            ColorMaterial::default()
+        }
+
+        fn activate(
+            &self,
+            _entity: Entity,
+            _parameters: TileParameters,
+            _shooter: Option<Entity>,
+        ) -> impl Command {
+            |_: &mut World| {}
+        }
+    }
+
+    #[test]
+    fn test_owner_lasers_do_not_wear_down_relays() {
+        let mut world = World::new();
+        world.init_resource::<Events<LaserHitEvent>>();
+        let owner = world.spawn_empty().id();
+        let rival = world.spawn_empty().id();
+        let relay = world
+            .spawn((
+                TestRelay,
+                Position::from(Hex::ORIGIN),
+                Owner::new(owner),
+                Health::new(2),
+            ))
+            .id();
+
+        for shooter in [owner, rival] {
+            world.send_event(LaserHitEvent {
+                strength: 1,
+                consumer: relay,
+                shooter,
+                kind: LaserKind::default(),
+            });
+        }
+        world.run_system_once(TilePlugin::<TestRelay>::handle_hit_tiles);
+
+        assert_eq!(**world.get::<Health>(relay).unwrap(), 1);
+    }
 }
@@ -0,0 +1,398 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use bevy::prelude::{
+    Added, Changed, Commands, Component, Deref, DerefMut, Entity, Event, EventWriter, Or, Query,
+    Reflect, RemovedComponents, With,
+};
+use hexx::Hex;
+
+use game_loop::{InGame, Player};
+use lasers::Position;
+use tilemap::TilemapEntities;
+
+use crate::Owner;
+
+// Tilemap tiles controlled by a player
+#[derive(Clone, Debug, Default)]
+#[derive(Component, Deref, DerefMut)]
+pub struct Territory(HashSet<Entity>);
+
+impl Territory {
+    // Influence radius of tiles that do not declare their own
+    pub const RANGE: usize = 4;
+}
+
+// How far an owned tile spreads its owner's territory and how strongly it holds it against
+// other players
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Component, Reflect)]
+pub struct Influence {
+    pub radius: u32,
+    pub strength: usize,
+}
+
+impl Influence {
+    pub const fn new(radius: u32, strength: usize) -> Self {
+        Influence { radius, strength }
+    }
+}
+
+// Territory does not spread into or past tiles with this marker
+#[derive(Clone, Copy, Debug)]
+#[derive(Component, Reflect)]
+pub struct TerritoryBlocker;
+
+// Sent for every player whose territory changed, with the tilemap tiles gained and lost
+#[derive(Clone, Debug)]
+#[derive(Event)]
+pub struct TerritoryChanged {
+    pub game: Entity,
+    pub player: Entity,
+    pub gained: Vec<Entity>,
+    pub lost: Vec<Entity>,
+}
+
+#[derive(Clone, Debug)]
+struct InfluenceSource {
+    owner: Entity,
+    position: Hex,
+    influence: Influence,
+    reach: Vec<Hex>,
+}
+
+// Influence of every owned tile in a game, updated as tiles come and go rather than rebuilt
+#[derive(Clone, Debug, Default)]
+#[derive(Component)]
+pub struct TerritoryMap {
+    sources: HashMap<Entity, InfluenceSource>,
+    blockers: HashMap<Entity, Hex>,
+    // Summed influence strength of every player per hex
+    influence: HashMap<Hex, HashMap<Entity, usize>>,
+    owners: HashMap<Hex, Entity>,
+}
+
+impl TerritoryMap {
+    pub fn owner(&self, hex: &Hex) -> Option<Entity> {
+        self.owners.get(hex).copied()
+    }
+
+    // Strength of every player's influence over the hex
+    pub fn influence(&self, hex: &Hex) -> impl Iterator<Item = (Entity, usize)> + '_ {
+        self.influence.get(hex).into_iter().flat_map(|players| {
+            players
+                .iter()
+                .map(|(player, strength)| (*player, *strength))
+        })
+    }
+
+    fn add_source(
+        &mut self,
+        source: Entity,
+        owner: Entity,
+        position: Hex,
+        influence: Influence,
+        in_map: &impl Fn(&Hex) -> bool,
+    ) -> Vec<Hex> {
+        let reach = self.spread(position, influence.radius, in_map);
+        for hex in &reach {
+            *self
+                .influence
+                .entry(*hex)
+                .or_default()
+                .entry(owner)
+                .or_default() += influence.strength;
+        }
+        self.sources.insert(
+            source,
+            InfluenceSource {
+                owner,
+                position,
+                influence,
+                reach: reach.clone(),
+            },
+        );
+        reach
+    }
+
+    fn remove_source(&mut self, source: Entity) -> Vec<Hex> {
+        let Some(removed) = self.sources.remove(&source) else {
+            return Vec::new();
+        };
+        for hex in &removed.reach {
+            if let Some(players) = self.influence.get_mut(hex) {
+                if let Some(strength) = players.get_mut(&removed.owner) {
+                    *strength = strength.saturating_sub(removed.influence.strength);
+                    if *strength == 0 {
+                        players.remove(&removed.owner);
+                    }
+                }
+            }
+        }
+        removed.reach
+    }
+
+    // Spreads outwards from the tile one ring at a time, going around blocked hexes
+    fn spread(&self, position: Hex, radius: u32, in_map: &impl Fn(&Hex) -> bool) -> Vec<Hex> {
+        let blocked = self.blockers.values().collect::<HashSet<_>>();
+        let mut reached = HashSet::from([position]);
+        let mut frontier = VecDeque::from([(position, 0)]);
+
+        while let Some((hex, distance)) = frontier.pop_front() {
+            if distance == radius {
+                continue;
+            }
+            for neighbor in hex.all_neighbors() {
+                if !in_map(&neighbor) || blocked.contains(&neighbor) || reached.contains(&neighbor)
+                {
+                    continue;
+                }
+                reached.insert(neighbor);
+                frontier.push_back((neighbor, distance + 1));
+            }
+        }
+
+        reached.into_iter().collect()
+    }
+
+    // Sources close enough to the hex that a blocker appearing or disappearing there could change
+    // their reach
+    fn sources_near(&self, hex: Hex) -> Vec<Entity> {
+        self.sources
+            .iter()
+            .filter(|(_, source)| {
+                source.position.unsigned_distance_to(hex) <= source.influence.radius
+            })
+            .map(|(entity, _)| *entity)
+            .collect()
+    }
+
+    fn respread(&mut self, sources: Vec<Entity>, in_map: &impl Fn(&Hex) -> bool) -> Vec<Hex> {
+        let mut touched = Vec::new();
+        for source in sources {
+            let Some(InfluenceSource {
+                owner,
+                position,
+                influence,
+                ..
+            }) = self.sources.get(&source).cloned()
+            else {
+                continue;
+            };
+            touched.extend(self.remove_source(source));
+            touched.extend(self.add_source(source, owner, position, influence, in_map));
+        }
+        touched
+    }
+
+    // The strongest influence over a hex controls it, hexes held equally by several players are
+    // contested and belong to nobody
+    fn resolve(&mut self, hexes: HashSet<Hex>) -> Vec<(Hex, Option<Entity>, Option<Entity>)> {
+        let mut changes = Vec::new();
+        for hex in hexes {
+            let strongest = self.influence.get(&hex).and_then(|players| {
+                let strength = players.values().copied().max()?;
+                let mut leaders = players
+                    .iter()
+                    .filter(|(_, player_strength)| **player_strength == strength);
+                match (leaders.next(), leaders.next()) {
+                    (Some((leader, _)), None) => Some(*leader),
+                    _ => None,
+                }
+            });
+            let previous = match strongest {
+                Some(owner) => self.owners.insert(hex, owner),
+                None => self.owners.remove(&hex),
+            };
+            if previous != strongest {
+                changes.push((hex, previous, strongest));
+            }
+        }
+        changes
+    }
+}
+
+// Recalculates only the hexes affected by influencing tiles and blockers that were spawned,
+// claimed or destroyed since the last run
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+pub(crate) fn update_territories(
+    mut commands: Commands,
+    tilemaps: Query<&TilemapEntities>,
+    mut games: Query<(Entity, &mut TerritoryMap)>,
+    sources: Query<
+        (Entity, &Position, &Owner, &Influence, &InGame),
+        Or<(Changed<Owner>, Added<Influence>)>,
+    >,
+    blockers: Query<(Entity, &Position, &InGame), Added<TerritoryBlocker>>,
+    mut removed_sources: RemovedComponents<Influence>,
+    mut removed_blockers: RemovedComponents<TerritoryBlocker>,
+    mut players: Query<Option<&mut Territory>, With<Player>>,
+    mut territory_changes: EventWriter<TerritoryChanged>,
+) {
+    let Ok(tilemap) = tilemaps.get_single() else {
+        return;
+    };
+    let in_map = |hex: &Hex| tilemap.tiles.contains_key(hex);
+    let removed_sources = removed_sources.read().collect::<Vec<_>>();
+    let removed_blockers = removed_blockers.read().collect::<Vec<_>>();
+
+    for (game, mut map) in &mut games {
+        let mut touched = HashSet::new();
+
+        for blocker in &removed_blockers {
+            if let Some(hex) = map.blockers.remove(blocker) {
+                let nearby = map.sources_near(hex);
+                touched.extend(map.respread(nearby, &in_map));
+            }
+        }
+        for (blocker, position, _) in blockers.iter().filter(|(.., in_game)| ***in_game == game) {
+            map.blockers.insert(blocker, **position);
+            let nearby = map.sources_near(**position);
+            touched.extend(map.respread(nearby, &in_map));
+        }
+
+        for source in &removed_sources {
+            touched.extend(map.remove_source(*source));
+        }
+        for (source, position, owner, influence, _) in
+            sources.iter().filter(|(.., in_game)| ***in_game == game)
+        {
+            touched.extend(map.remove_source(source));
+            touched.extend(map.add_source(source, **owner, **position, *influence, &in_map));
+        }
+
+        if touched.is_empty() {
+            continue;
+        }
+
+        let mut deltas: HashMap<Entity, (Vec<Entity>, Vec<Entity>)> = HashMap::new();
+        for (hex, previous, current) in map.resolve(touched) {
+            let Some(tile) = tilemap.tiles.get(&hex) else {
+                continue;
+            };
+            if let Some(previous) = previous {
+                deltas.entry(previous).or_default().1.push(*tile);
+            }
+            if let Some(current) = current {
+                deltas.entry(current).or_default().0.push(*tile);
+            }
+        }
+
+        for (player, (gained, lost)) in deltas {
+            match players.get_mut(player) {
+                Ok(Some(mut territory)) => {
+                    for tile in &lost {
+                        territory.remove(tile);
+                    }
+                    territory.extend(gained.iter().copied());
+                }
+                Ok(None) => {
+                    commands
+                        .entity(player)
+                        .insert(Territory(gained.iter().copied().collect()));
+                }
+                Err(_) => continue,
+            }
+            territory_changes.send(TerritoryChanged {
+                game,
+                player,
+                gained,
+                lost,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use bevy::prelude::Entity;
+    use hexx::Hex;
+
+    use super::{Influence, TerritoryMap};
+
+    fn in_map(hex: &Hex) -> bool {
+        hex.unsigned_distance_to(Hex::ORIGIN) <= 4
+    }
+
+    #[test]
+    fn test_spread_goes_around_blockers() {
+        let player = Entity::from_raw(1);
+        let blocker = Hex::new(0, -1);
+        let mut map = TerritoryMap::default();
+        map.blockers.insert(Entity::from_raw(10), blocker);
+
+        let reach = map.add_source(
+            Entity::from_raw(2),
+            player,
+            Hex::ORIGIN,
+            Influence::new(2, 1),
+            &in_map,
+        );
+
+        assert!(!reach.contains(&blocker));
+        // Right behind the blocker, three steps away when going around it
+        assert!(!reach.contains(&Hex::new(0, -2)));
+        // Just as far away but reachable without crossing the blocker
+        assert!(reach.contains(&Hex::new(1, -2)));
+        assert!(reach.contains(&Hex::new(-1, -1)));
+
+        let reach = map.respread(vec![Entity::from_raw(2)], &in_map);
+        assert!(!reach.contains(&Hex::new(0, -2)));
+        map.blockers.clear();
+        let reach = map.respread(vec![Entity::from_raw(2)], &in_map);
+        assert!(reach.contains(&blocker));
+        assert!(reach.contains(&Hex::new(0, -2)));
+    }
+
+    #[test]
+    fn test_tied_influence_leaves_hex_unowned() {
+        let first = Entity::from_raw(1);
+        let second = Entity::from_raw(2);
+        let mut map = TerritoryMap::default();
+
+        let mut touched = HashSet::new();
+        touched.extend(map.add_source(
+            Entity::from_raw(3),
+            first,
+            Hex::new(-1, 0),
+            Influence::new(1, 2),
+            &in_map,
+        ));
+        touched.extend(map.add_source(
+            Entity::from_raw(4),
+            second,
+            Hex::new(1, 0),
+            Influence::new(1, 2),
+            &in_map,
+        ));
+        map.resolve(touched);
+
+        assert_eq!(map.owner(&Hex::ORIGIN), None);
+        assert_eq!(map.influence(&Hex::ORIGIN).count(), 2);
+        assert_eq!(map.owner(&Hex::new(-1, 0)), Some(first));
+        assert_eq!(map.owner(&Hex::new(1, 0)), Some(second));
+    }
+
+    #[test]
+    fn test_removed_source_returns_influence_to_zero() {
+        let player = Entity::from_raw(1);
+        let source = Entity::from_raw(2);
+        let mut map = TerritoryMap::default();
+
+        let reach = map.add_source(source, player, Hex::ORIGIN, Influence::new(1, 3), &in_map);
+        let gained = map.resolve(reach.iter().copied().collect());
+        assert_eq!(gained.len(), 7);
+        assert!(gained
+            .iter()
+            .all(|(_, previous, current)| previous.is_none() && *current == Some(player)));
+
+        let reach = map.remove_source(source);
+        let lost = map.resolve(reach.iter().copied().collect());
+        assert_eq!(lost.len(), 7);
+        for hex in reach {
+            assert_eq!(map.influence(&hex).count(), 0);
+            assert_eq!(map.owner(&hex), None);
+        }
+    }
+}
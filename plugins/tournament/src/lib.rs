@@ -0,0 +1,381 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use rand::{seq::IteratorRandom, Rng};
+
+use entropy::EntropyBundle;
+use game_loop::{
+    DrawingCompleteEvent, GameLoopSystems, GamePhase, GamePlayers, InGame, Player, Ready, Turn,
+};
+use hq::HQTile;
+use laser_tower::LaserTower;
+use merchandise::{
+    MerchId, MerchRegistry, MerchStock, MerchSystems, Money, MoneyChangeReason, MoneyChanged,
+    Purchase,
+};
+use tilemap::EmptyTile;
+use tiles::{
+    lasers::{Direction, Position, Rotation},
+    Owner, Territory, TileSpawnEvent,
+};
+
+mod report;
+pub use report::*;
+
+// Drives headless matches: scripted players take their turns, the drawing phase is skipped and
+// the results of the match are collected into `MatchStats`
+pub struct TournamentPlugin {
+    settings: MatchSettings,
+}
+
+impl TournamentPlugin {
+    pub fn new(settings: MatchSettings) -> Self {
+        TournamentPlugin { settings }
+    }
+}
+
+impl Plugin for TournamentPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.settings.clone())
+            .init_resource::<MatchStats>()
+            .add_systems(
+                Update,
+                (
+                    Self::assign_strategies,
+                    Self::play_turns.before(MerchSystems),
+                    Self::orient_purchases,
+                    Self::skip_drawing,
+                    Self::record_purchases,
+                    Self::record_deposit_payouts,
+                    Self::check_match_end,
+                )
+                    .chain()
+                    .after(GameLoopSystems)
+                    .in_set(TournamentSystems),
+            );
+    }
+}
+
+impl TournamentPlugin {
+    fn assign_strategies(
+        mut commands: Commands,
+        games: Query<&GamePlayers, Added<GamePlayers>>,
+        settings: Res<MatchSettings>,
+    ) {
+        for players in &games {
+            for (index, player) in players.iter().enumerate() {
+                let strategy = settings
+                    .strategies
+                    .get(index % settings.strategies.len().max(1))
+                    .copied()
+                    .unwrap_or_default();
+                commands.entity(*player).insert(strategy);
+            }
+        }
+    }
+
+    fn play_turns(
+        mut commands: Commands,
+        mut purchases: EventWriter<Purchase>,
+        mut games: Query<(&GamePhase, &Turn, &MerchStock, &mut EntropyBundle)>,
+        players: Query<(Entity, &Strategy, &Money, &Territory, &InGame), Without<Ready>>,
+        empty_tiles: Query<(Entity, &tilemap::Tile), With<EmptyTile>>,
+        hqs: Query<(&Position, &Owner), With<HQTile>>,
+        registry: Res<MerchRegistry>,
+    ) {
+        for (player, strategy, money, territory, in_game) in &players {
+            let Ok((phase, turn, stock, mut entropy)) = games.get_mut(**in_game) else {
+                continue;
+            };
+            if !matches!(phase, GamePhase::Choose) {
+                continue;
+            }
+
+            let mut candidates = empty_tiles
+                .iter()
+                .filter(|(tile, _)| territory.contains(tile))
+                .collect::<Vec<_>>();
+            let mut budget = **money;
+
+            match strategy {
+                Strategy::Passive => {}
+                Strategy::Random => {
+                    for _ in 0..Strategy::MAX_PURCHASES {
+                        // Sorted so the same seed buys the same merch on every build
+                        let Some(merch) = registry
+                            .sorted()
+                            .into_iter()
+                            .map(|(_, merch)| merch)
+                            .filter(|merch| {
+                                *merch.price() <= budget
+                                    && registry.is_available(merch, **turn, stock)
+                            })
+                            .choose(&mut entropy.entropy)
+                        else {
+                            break;
+                        };
+                        // Each purchase takes a different hex
+                        if candidates.is_empty() {
+                            break;
+                        }
+                        let index = entropy.entropy.gen_range(0..candidates.len());
+                        let (tile, _) = candidates.swap_remove(index);
+                        budget -= *merch.price();
+                        purchases.send(Purchase::new(player, merch.clone(), tile));
+                    }
+                }
+                Strategy::Aggressive => {
+                    let enemy_hqs = hqs
+                        .iter()
+                        .filter(|(_, owner)| ***owner != player)
+                        .map(|(position, _)| **position)
+                        .collect::<Vec<_>>();
+                    // Build as close to the enemy as the territory allows
+                    let target = candidates.iter().min_by_key(|(_, hex)| {
+                        enemy_hqs
+                            .iter()
+                            .map(|enemy| enemy.unsigned_distance_to(***hex))
+                            .min()
+                            .unwrap_or(u32::MAX)
+                    });
+                    if let (Some(tower), Some((tile, _))) = (
+                        registry
+                            .get::<LaserTower>()
+                            .filter(|tower| *tower.price() <= budget),
+                        target,
+                    ) {
+                        purchases.send(Purchase::new(player, tower.clone(), *tile));
+                    }
+                }
+            }
+
+            commands.entity(player).insert(Ready);
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn orient_purchases(
+        mut purchased: Query<
+            (
+                &Position,
+                &Owner,
+                &InGame,
+                Option<&mut Direction>,
+                Option<&mut Rotation>,
+            ),
+            Added<Owner>,
+        >,
+        players: Query<&Strategy>,
+        hqs: Query<(&Position, &Owner), With<HQTile>>,
+        mut games: Query<&mut EntropyBundle>,
+    ) {
+        for (position, owner, in_game, direction, rotation) in &mut purchased {
+            let Ok(strategy) = players.get(**owner) else {
+                continue;
+            };
+            let Ok(mut entropy) = games.get_mut(**in_game) else {
+                continue;
+            };
+
+            if let Some(mut direction) = direction {
+                let nearest_enemy = hqs
+                    .iter()
+                    .filter(|(_, hq_owner)| hq_owner != &owner)
+                    .map(|(hq_position, _)| **hq_position)
+                    .min_by_key(|hex| hex.unsigned_distance_to(**position));
+                *direction = match (strategy, nearest_enemy) {
+                    (Strategy::Aggressive, Some(target)) => Direction::ALL
+                        .into_iter()
+                        .min_by_key(|direction| {
+                            position
+                                .neighbor(direction.as_hex())
+                                .unsigned_distance_to(target)
+                        })
+                        .unwrap_or_default(),
+                    _ => Direction::ALL[entropy.entropy.gen_range(0..Direction::ALL.len())],
+                };
+            }
+
+            if let Some(mut rotation) = rotation {
+                *rotation = Rotation::new(entropy.entropy.gen_range(1..6));
+            }
+        }
+    }
+
+    // There is nothing to draw in a headless match, so the drawing phase completes immediately
+    fn skip_drawing(
+        games: Query<(Entity, &GamePhase)>,
+        mut events: EventWriter<DrawingCompleteEvent>,
+    ) {
+        for (game, phase) in &games {
+            if matches!(phase, GamePhase::Draw) {
+                events.send(DrawingCompleteEvent { game });
+            }
+        }
+    }
+
+    fn record_purchases(
+        mut tile_spawns: EventReader<TileSpawnEvent>,
+        registry: Res<MerchRegistry>,
+        players: Query<(), With<Player>>,
+        mut stats: ResMut<MatchStats>,
+    ) {
+        for TileSpawnEvent { tile_id, owner, .. } in tile_spawns.read() {
+            if !players.contains(*owner) {
+                continue;
+            }
+            if let Some((_, merch)) = registry.iter().find(|(id, _)| *id == tile_id) {
+                *stats.purchases.entry(merch.id()).or_default() += 1;
+            }
+        }
+    }
+
+    fn record_deposit_payouts(
+        mut money_changes: EventReader<MoneyChanged>,
+        games: Query<&GamePlayers>,
+        mut stats: ResMut<MatchStats>,
+    ) {
+        for MoneyChanged { player, delta, .. } in money_changes
+            .read()
+            .filter(|change| matches!(change.reason, MoneyChangeReason::Deposit))
+        {
+            let Some(index) = games
+                .iter()
+                .find_map(|players| players.iter().position(|entity| entity == player))
+            else {
+                continue;
+            };
+            if stats.deposit_income.len() <= index {
+                stats.deposit_income.resize(index + 1, 0);
+            }
+            stats.deposit_income[index] += delta.unsigned_abs();
+        }
+    }
+
+    fn check_match_end(
+        games: Query<(&GamePlayers, &Turn)>,
+        hqs: Query<&Owner, With<HQTile>>,
+        settings: Res<MatchSettings>,
+        mut stats: ResMut<MatchStats>,
+    ) {
+        if stats.finished {
+            return;
+        }
+        let Ok((players, turn)) = games.get_single() else {
+            return;
+        };
+        stats.turns = **turn;
+
+        let standing = players
+            .iter()
+            .enumerate()
+            .filter(|(_, player)| hqs.iter().any(|owner| **owner == **player))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if !stats.hqs_spawned {
+            stats.hqs_spawned = standing.len() == players.len();
+            return;
+        }
+
+        if standing.len() <= 1 {
+            stats.finished = true;
+            stats.winner = standing.first().copied();
+            info!(
+                "Match finished on turn {} with winner {:?}",
+                **turn, stats.winner
+            );
+        } else if **turn >= settings.max_turns {
+            stats.finished = true;
+            info!("Match reached the turn limit without a winner");
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(SystemSet)]
+pub struct TournamentSystems;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Component, Reflect)]
+pub enum Strategy {
+    // Never buys anything, useful as a baseline
+    Passive,
+    // Buys random affordable merch on random tiles in its territory
+    #[default]
+    Random,
+    // Builds laser towers as close to the nearest enemy HQ as possible, aimed at it
+    Aggressive,
+}
+
+impl Strategy {
+    pub const ALL: [Self; 3] = [Self::Passive, Self::Random, Self::Aggressive];
+    const MAX_PURCHASES: usize = 2;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Passive => "passive",
+            Self::Random => "random",
+            Self::Aggressive => "aggressive",
+        }
+    }
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|strategy| strategy.name() == value)
+            .ok_or_else(|| format!("Unknown strategy {value}"))
+    }
+}
+
+#[derive(Clone, Debug)]
+#[derive(Resource)]
+pub struct MatchSettings {
+    pub strategies: Vec<Strategy>,
+    pub max_turns: usize,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        MatchSettings {
+            strategies: vec![Strategy::Random, Strategy::Aggressive],
+            max_turns: 50,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+#[derive(Resource)]
+pub struct MatchStats {
+    pub finished: bool,
+    pub hqs_spawned: bool,
+    pub turns: usize,
+    pub winner: Option<usize>,
+    pub purchases: BTreeMap<MerchId, usize>,
+    // Money moved from resource deposits to each player, indexed like `GamePlayers`
+    pub deposit_income: Vec<usize>,
+}
+
+impl MatchStats {
+    pub fn into_record(self, seed: u64, settings: &MatchSettings) -> MatchRecord {
+        MatchRecord {
+            seed,
+            turns: self.turns,
+            winner: self.winner,
+            strategies: settings.strategies.clone(),
+            purchases: self.purchases,
+            deposit_income: self.deposit_income,
+        }
+    }
+}
+
+// Names for every registered merch, used to label report columns
+pub fn merch_names(registry: &MerchRegistry) -> BTreeMap<MerchId, String> {
+    registry
+        .iter()
+        .map(|(_, merch)| (merch.id(), merch.name().to_string()))
+        .collect()
+}
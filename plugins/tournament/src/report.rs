@@ -0,0 +1,365 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    string::FromUtf8Error,
+};
+
+use serde::Serialize;
+use thiserror::Error;
+
+use merchandise::MerchId;
+
+use crate::Strategy;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    Csv,
+    #[default]
+    Json,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(format!(
+                "Unknown report format {value}, expected csv or json"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchRecord {
+    pub seed: u64,
+    pub turns: usize,
+    // Index into `strategies` of the last player with a standing HQ, if any
+    pub winner: Option<usize>,
+    pub strategies: Vec<Strategy>,
+    pub purchases: BTreeMap<MerchId, usize>,
+    pub deposit_income: Vec<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TournamentReport {
+    pub merch_names: BTreeMap<MerchId, String>,
+    pub matches: Vec<MatchRecord>,
+}
+
+impl TournamentReport {
+    pub fn new(merch_names: BTreeMap<MerchId, String>) -> Self {
+        TournamentReport {
+            merch_names,
+            matches: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, record: MatchRecord) {
+        self.matches.push(record);
+    }
+
+    pub fn render(&self, format: ReportFormat) -> Result<String, ReportError> {
+        match format {
+            ReportFormat::Csv => self.to_csv(),
+            ReportFormat::Json => self.to_json(),
+        }
+    }
+
+    pub fn average_turns(&self) -> f64 {
+        if self.matches.is_empty() {
+            return 0.;
+        }
+        let total = self
+            .matches
+            .iter()
+            .map(|record| record.turns)
+            .sum::<usize>();
+        total as f64 / self.matches.len() as f64
+    }
+
+    // Fraction of the matches each strategy played in that it also won
+    pub fn win_rates(&self) -> BTreeMap<Strategy, f64> {
+        let mut played: HashMap<Strategy, usize> = HashMap::new();
+        let mut won: HashMap<Strategy, usize> = HashMap::new();
+        for record in &self.matches {
+            for strategy in &record.strategies {
+                *played.entry(*strategy).or_default() += 1;
+            }
+            if let Some(strategy) = record
+                .winner
+                .and_then(|winner| record.strategies.get(winner))
+            {
+                *won.entry(*strategy).or_default() += 1;
+            }
+        }
+        played
+            .into_iter()
+            .map(|(strategy, count)| {
+                let wins = won.get(&strategy).copied().unwrap_or_default();
+                (strategy, wins as f64 / count as f64)
+            })
+            .collect()
+    }
+
+    pub fn draws(&self) -> usize {
+        self.matches
+            .iter()
+            .filter(|record| record.winner.is_none())
+            .count()
+    }
+
+    pub fn purchase_totals(&self) -> BTreeMap<MerchId, usize> {
+        let mut totals: BTreeMap<MerchId, usize> =
+            self.merch_names.keys().map(|id| (*id, 0)).collect();
+        for record in &self.matches {
+            for (id, count) in &record.purchases {
+                *totals.entry(*id).or_default() += count;
+            }
+        }
+        totals
+    }
+
+    pub fn deposit_income_total(&self) -> usize {
+        self.matches
+            .iter()
+            .flat_map(|record| record.deposit_income.iter())
+            .sum()
+    }
+
+    fn players(&self) -> usize {
+        self.matches
+            .iter()
+            .map(|record| record.strategies.len())
+            .max()
+            .unwrap_or_default()
+    }
+
+    // One row per match, with a column per player slot and per registered merch
+    pub fn to_csv(&self) -> Result<String, ReportError> {
+        let players = self.players();
+        let mut writer = csv::Writer::from_writer(Vec::new());
+
+        let mut header = vec![
+            "seed".to_string(),
+            "turns".to_string(),
+            "winner".to_string(),
+        ];
+        header.extend((0..players).map(|index| format!("strategy_{index}")));
+        header.extend((0..players).map(|index| format!("deposit_income_{index}")));
+        header.extend(
+            self.merch_names
+                .values()
+                .map(|name| format!("purchases_{}", name.replace(' ', "_").to_lowercase())),
+        );
+        writer.write_record(&header)?;
+
+        for record in &self.matches {
+            let mut row = vec![
+                record.seed.to_string(),
+                record.turns.to_string(),
+                record
+                    .winner
+                    .map(|winner| winner.to_string())
+                    .unwrap_or_default(),
+            ];
+            row.extend((0..players).map(|index| {
+                record
+                    .strategies
+                    .get(index)
+                    .map(|strategy| strategy.name().to_string())
+                    .unwrap_or_default()
+            }));
+            row.extend((0..players).map(|index| {
+                record
+                    .deposit_income
+                    .get(index)
+                    .copied()
+                    .unwrap_or_default()
+                    .to_string()
+            }));
+            row.extend(self.merch_names.keys().map(|id| {
+                record
+                    .purchases
+                    .get(id)
+                    .copied()
+                    .unwrap_or_default()
+                    .to_string()
+            }));
+            writer.write_record(&row)?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|error| csv::Error::from(error.into_error()))?;
+        Ok(String::from_utf8(bytes)?)
+    }
+
+    // Summary statistics followed by the per-match records
+    pub fn to_json(&self) -> Result<String, ReportError> {
+        let report = JsonReport {
+            matches_played: self.matches.len(),
+            draws: self.draws(),
+            average_turns: self.average_turns(),
+            win_rates: self
+                .win_rates()
+                .into_iter()
+                .map(|(strategy, rate)| (strategy.name(), rate))
+                .collect(),
+            purchases: self.named_counts(&self.purchase_totals()),
+            deposit_income: self.deposit_income_total(),
+            records: self
+                .matches
+                .iter()
+                .map(|record| JsonRecord {
+                    seed: record.seed,
+                    turns: record.turns,
+                    winner: record.winner,
+                    strategies: record.strategies.iter().map(Strategy::name).collect(),
+                    deposit_income: &record.deposit_income,
+                    purchases: self.named_counts(&record.purchases),
+                })
+                .collect(),
+        };
+        let mut json = serde_json::to_string_pretty(&report)?;
+        json.push('\n');
+        Ok(json)
+    }
+
+    fn named_counts(&self, counts: &BTreeMap<MerchId, usize>) -> BTreeMap<&str, usize> {
+        let mut named = BTreeMap::new();
+        for (id, count) in counts {
+            let name = self.merch_names.get(id).map_or("Unknown", String::as_str);
+            *named.entry(name).or_default() += count;
+        }
+        named
+    }
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    matches_played: usize,
+    draws: usize,
+    average_turns: f64,
+    win_rates: BTreeMap<&'static str, f64>,
+    purchases: BTreeMap<&'a str, usize>,
+    deposit_income: usize,
+    records: Vec<JsonRecord<'a>>,
+}
+
+#[derive(Serialize)]
+struct JsonRecord<'a> {
+    seed: u64,
+    turns: usize,
+    winner: Option<usize>,
+    strategies: Vec<&'static str>,
+    deposit_income: &'a [usize],
+    purchases: BTreeMap<&'a str, usize>,
+}
+
+#[derive(Debug)]
+#[derive(Error)]
+pub enum ReportError {
+    #[error("Failed to write the CSV report: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Failed to write the JSON report: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("The report is not valid UTF-8: {0}")]
+    Utf8(#[from] FromUtf8Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use merchandise::MerchId;
+
+    use super::{MatchRecord, TournamentReport};
+    use crate::Strategy;
+
+    const TOWER: MerchId = MerchId::from_key("laser_tower");
+    const MIRROR: MerchId = MerchId::from_key("reflector");
+
+    fn test_report() -> TournamentReport {
+        let mut report = TournamentReport::new(BTreeMap::from([
+            (TOWER, "Laser Tower".to_string()),
+            (MIRROR, "Mirror, \"deluxe\"".to_string()),
+        ]));
+        report.push(MatchRecord {
+            seed: 1,
+            turns: 4,
+            winner: Some(1),
+            strategies: vec![Strategy::Passive, Strategy::Aggressive],
+            purchases: BTreeMap::from([(TOWER, 3)]),
+            deposit_income: vec![0, 5],
+        });
+        report.push(MatchRecord {
+            seed: 2,
+            turns: 8,
+            winner: None,
+            strategies: vec![Strategy::Random, Strategy::Aggressive],
+            purchases: BTreeMap::from([(TOWER, 1), (MIRROR, 2)]),
+            deposit_income: vec![2, 3],
+        });
+        report
+    }
+
+    #[test]
+    fn test_report_aggregation() {
+        let report = test_report();
+
+        assert_eq!(report.average_turns(), 6.);
+        assert_eq!(report.draws(), 1);
+        assert_eq!(report.deposit_income_total(), 10);
+        assert_eq!(
+            report.purchase_totals(),
+            BTreeMap::from([(TOWER, 4), (MIRROR, 2)])
+        );
+        assert_eq!(
+            report.win_rates(),
+            BTreeMap::from([
+                (Strategy::Passive, 0.),
+                (Strategy::Random, 0.),
+                (Strategy::Aggressive, 0.5),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_empty_report_aggregation() {
+        let report = TournamentReport::new(BTreeMap::from([(TOWER, "Laser Tower".to_string())]));
+
+        assert_eq!(report.average_turns(), 0.);
+        assert_eq!(report.draws(), 0);
+        assert!(report.win_rates().is_empty());
+        assert_eq!(report.purchase_totals(), BTreeMap::from([(TOWER, 0)]));
+    }
+
+    #[test]
+    fn test_json_report_escapes_names() {
+        let json = test_report().to_json().unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(parsed["matches_played"], 2);
+        assert_eq!(parsed["purchases"]["Mirror, \"deluxe\""], 2);
+        assert_eq!(parsed["records"][1]["winner"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_csv_report_escapes_names() {
+        let csv = test_report().to_csv().unwrap();
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+
+        let header = reader.headers().unwrap().clone();
+        assert_eq!(header.len(), 9);
+        let mirror = header
+            .iter()
+            .position(|column| column == "purchases_mirror,_\"deluxe\"")
+            .unwrap();
+        let rows = reader.records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[1][mirror], "2");
+    }
+}
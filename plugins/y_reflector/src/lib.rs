@@ -1,14 +1,192 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
+use std::f32::consts::PI;
+
+use bevy::{
+    color::palettes,
+    ecs::{system::SystemState, world::Command},
+    prelude::*,
+    sprite::MaterialMesh2dBundle,
+};
+
+use game_loop::InGame;
+use merchandise::{MerchAppExt, Merchandise, Money};
+use shop::JustPurchased;
+use tilemap::TilemapLayout;
+use tiles::{
+    lasers::{Consumption, Direction, Position, YReflection},
+    tile_material, Owner, Tile, TileParameters, TilePlugin,
+};
+
+pub struct YReflectorPlugin;
+
+impl Plugin for YReflectorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(TilePlugin::<YReflectorTile>::default())
+            .add_systems(Update, Self::update_marker);
+        app.define_merchandise::<YReflectorTile>();
+    }
+}
+
+impl YReflectorPlugin {
+    fn update_marker(
+        tiles: Query<&YReflection, (With<YReflectorTile>, Changed<YReflection>)>,
+        mut markers: Query<(&Parent, &mut Transform), With<YReflectorMarker>>,
+    ) {
+        for (parent, mut transform) in &mut markers {
+            if let Ok(tilt) = tiles.get(**parent) {
+                *transform = match *tilt {
+                    YReflection::LeftTilt => {
+                        Transform::from_rotation(Quat::from_rotation_z(PI / 6.))
+                    }
+                    YReflection::RightTilt => {
+                        Transform::from_rotation(Quat::from_rotation_z(-PI / 6.))
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+#[derive(Component, Reflect)]
+pub struct YReflectorTile;
+
+impl YReflectorTile {
+    // Angles of the arms of the "Y" marker before it is tilted, measured from north
+    const ARMS: [f32; 3] = [PI / 3., PI, 5. * PI / 3.];
+}
+
+impl Tile for YReflectorTile {
+    const MAX_HEALTH: Option<usize> = Some(2);
+    const OWNER_IMMUNE: bool = true;
+    const TEXTURE: Option<&'static str> = Some("tiles/y_reflector.png");
+
+    fn spawn(position: Position, player: Entity, _game: Entity) -> impl Command {
+        YReflectorSpawn { position, player }
+    }
+
+    fn material(_asset_server: &AssetServer) -> ColorMaterial {
+        Color::Srgba(palettes::css::TEAL).into()
+    }
+
+    fn activate(
+        &self,
+        entity: Entity,
+        parameters: TileParameters,
+        _shooter: Option<Entity>,
+    ) -> impl Command {
+        YReflectorActivate {
+            tile: entity,
+            position: parameters.position,
+            tilt: parameters
+                .tilt
+                .unwrap_or_else(|| panic!("Y reflector needs a tilt")),
+            vulnerable: Self::vulnerable(&parameters),
+        }
+    }
+
+    // Every laser from another player reflected by the tile wears it down
+    fn vulnerable(_parameters: &TileParameters) -> Vec<Direction> {
+        Direction::ALL.to_vec()
+    }
+}
+
+impl Merchandise for YReflectorTile {
+    const KEY: &'static str = "y_reflector";
+    const PRICE: Money = Money::new(5);
+    const NAME: &'static str = "Y Reflector Tower";
+
+    fn material(asset_server: &AssetServer) -> ColorMaterial {
+        let mut base = tile_material::<Self>(asset_server, 0.);
+        base.color.set_alpha(0.6);
+        base
+    }
+}
+
+pub struct YReflectorSpawn {
+    position: Position,
+    player: Entity,
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Command for YReflectorSpawn {
+    #[allow(clippy::type_complexity)]
+    fn apply(self, world: &mut World) {
+        let mut system_state: SystemState<(
+            Query<&TilemapLayout>,
+            ResMut<Assets<Mesh>>,
+            ResMut<Assets<ColorMaterial>>,
+        )> = SystemState::new(world);
 
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
+        let (layout, mut meshes, mut materials) = system_state.get_mut(world);
+
+        let Ok(translation) = layout
+            .get_single()
+            .map(|layout| layout.hex_to_world_pos(*self.position).extend(11.))
+        else {
+            info!("Did not get the single tilemap layout for the game");
+            return;
+        };
+
+        let hub = meshes.add(Circle::new(4.));
+        let arm = meshes.add(Rectangle::new(5., 30.));
+        let black = materials.add(Color::BLACK);
+        let tilt = YReflection::default();
+
+        if let Some(game) = world.get::<InGame>(self.player) {
+            world
+                .spawn((
+                    YReflectorTile,
+                    self.position,
+                    tilt,
+                    Owner::new(self.player),
+                    game.clone(),
+                    Transform::from_translation(translation),
+                    GlobalTransform::from_translation(translation),
+                    JustPurchased,
+                ))
+                .with_children(|builder| {
+                    builder
+                        .spawn((
+                            YReflectorMarker,
+                            MaterialMesh2dBundle {
+                                mesh: hub.into(),
+                                material: black.clone(),
+                                transform: Transform::default(),
+                                ..default()
+                            },
+                        ))
+                        .with_children(|builder| {
+                            for angle in YReflectorTile::ARMS {
+                                let rotation = Quat::from_rotation_z(angle);
+                                builder.spawn(MaterialMesh2dBundle {
+                                    mesh: arm.clone().into(),
+                                    material: black.clone(),
+                                    transform: Transform::from_rotation(rotation)
+                                        .with_translation(rotation.mul_vec3(Vec3::Y * 15.)),
+                                    ..default()
+                                });
+                            }
+                        });
+                });
+        }
     }
 }
+
+pub struct YReflectorActivate {
+    tile: Entity,
+    position: Position,
+    tilt: YReflection,
+    vulnerable: Vec<Direction>,
+}
+
+impl Command for YReflectorActivate {
+    fn apply(self, world: &mut World) {
+        world.spawn((
+            self.tilt,
+            Consumption::passing_bundle(self.tile, self.vulnerable, self.position),
+        ));
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+#[derive(Component)]
+pub struct YReflectorMarker;
@@ -0,0 +1,166 @@
+// Runs seeded headless matches between scripted players and reports the results, e.g.
+// `cargo run --bin tournament -- --matches 100 --players random,aggressive --format csv`
+use bevy::{
+    asset::AssetPlugin,
+    prelude::{
+        App, AssetApp, ColorMaterial, HierarchyPlugin, Image, Mesh, MinimalPlugins, PluginGroup,
+        TransformPlugin,
+    },
+};
+
+use pewpewboom::{
+    camera::CameraPlugin,
+    controls::ControlsPlugin,
+    entropy::EntropyPlugin,
+    game_loop::GameInstanceBundle,
+    laser_visuals::LaserVisualPlugin,
+    merchandise::MerchRegistry,
+    shop::ShopPlugin,
+    tilemap::Tilemap,
+    tournament::{
+        merch_names, MatchSettings, MatchStats, ReportFormat, Strategy, TournamentPlugin,
+        TournamentReport,
+    },
+    PewPewBoomBuildingsPlugins, PewPewBoomPlugins,
+};
+
+// Upper bound on frames per match, in case a match stalls without reaching its turn limit
+const MAX_UPDATES: usize = 100_000;
+
+struct Options {
+    matches: usize,
+    seed: u64,
+    format: ReportFormat,
+    output: Option<String>,
+    settings: MatchSettings,
+}
+
+impl Options {
+    fn parse() -> Result<Options, String> {
+        let mut options = Options {
+            matches: 10,
+            seed: 0,
+            format: ReportFormat::default(),
+            output: None,
+            settings: MatchSettings::default(),
+        };
+
+        let mut args = std::env::args().skip(1);
+        while let Some(flag) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for {flag}"))
+            };
+            match flag.as_str() {
+                "--matches" => {
+                    options.matches = value()?.parse().map_err(|_| "Invalid --matches")?
+                }
+                "--seed" => options.seed = value()?.parse().map_err(|_| "Invalid --seed")?,
+                "--max-turns" => {
+                    options.settings.max_turns =
+                        value()?.parse().map_err(|_| "Invalid --max-turns")?
+                }
+                "--players" => {
+                    options.settings.strategies = value()?
+                        .split(',')
+                        .map(str::parse)
+                        .collect::<Result<_, _>>()?
+                }
+                "--format" => options.format = value()?.parse()?,
+                "--output" => options.output = Some(value()?),
+                _ => return Err(format!("Unknown argument {flag}")),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn match_app(seed: u64, settings: &MatchSettings) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+        HierarchyPlugin,
+    ))
+    .init_asset::<Image>()
+    .init_asset::<Mesh>()
+    .init_asset::<ColorMaterial>();
+    app.add_plugins((
+        PewPewBoomPlugins
+            .build()
+            .set(EntropyPlugin::new(seed.to_le_bytes()))
+            .disable::<CameraPlugin>()
+            .disable::<ShopPlugin>()
+            .disable::<ControlsPlugin>()
+            .disable::<LaserVisualPlugin>(),
+        PewPewBoomBuildingsPlugins,
+        TournamentPlugin::new(settings.clone()),
+    ));
+    app.world_mut().spawn(GameInstanceBundle::default());
+    app.world_mut().spawn(Tilemap::bundle());
+    app.finish();
+    app.cleanup();
+    app
+}
+
+fn main() {
+    let options = match Options::parse() {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("{error}");
+            eprintln!(
+                "usage: tournament [--matches N] [--seed S] [--max-turns T] [--players {}] [--format csv|json] [--output FILE]",
+                Strategy::ALL.map(|strategy| strategy.name()).join(",")
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let mut report: Option<TournamentReport> = None;
+    for index in 0..options.matches {
+        let seed = options.seed.wrapping_add(index as u64);
+        let mut app = match_app(seed, &options.settings);
+        for _ in 0..MAX_UPDATES {
+            app.update();
+            if app.world().resource::<MatchStats>().finished {
+                break;
+            }
+        }
+
+        let report = report.get_or_insert_with(|| {
+            TournamentReport::new(merch_names(app.world().resource::<MerchRegistry>()))
+        });
+        let stats = app
+            .world_mut()
+            .remove_resource::<MatchStats>()
+            .unwrap_or_default();
+        eprintln!(
+            "match {}/{} (seed {}): {} turns, winner {:?}",
+            index + 1,
+            options.matches,
+            seed,
+            stats.turns,
+            stats.winner
+        );
+        report.push(stats.into_record(seed, &options.settings));
+    }
+
+    let rendered = match report.unwrap_or_default().render(options.format) {
+        Ok(rendered) => rendered,
+        Err(error) => {
+            eprintln!("{error}");
+            std::process::exit(1);
+        }
+    };
+    match options.output {
+        Some(path) => {
+            if let Err(error) = std::fs::write(&path, rendered) {
+                eprintln!("Failed to write report to {path}: {error}");
+                std::process::exit(1);
+            }
+        }
+        None => print!("{rendered}"),
+    }
+}
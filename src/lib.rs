@@ -2,7 +2,10 @@ use bevy::{app::PluginGroupBuilder, prelude::*};
 
 pub use amplifier;
 pub use camera;
+pub use controls;
+pub use economy;
 pub use entropy;
+pub use events;
 pub use game_loop;
 pub use health;
 pub use hq;
@@ -11,8 +14,10 @@ pub use laser_visuals;
 pub use map_generator;
 pub use merchandise;
 pub use refractor;
+pub use shop;
 pub use tilemap;
 pub use tiles;
+pub use tournament;
 
 pub struct PewPewBoomPlugins;
 
@@ -24,14 +29,19 @@ impl PluginGroup for PewPewBoomPlugins {
             .add(tiles::TilesPlugin)
             .add(map_generator::MapGeneratorPlugin)
             .add(merchandise::MerchPlugin)
+            .add(economy::EconomyPlugin)
             .add(mountain::MountainPlugin)
+            .add(terrain::TerrainPlugin)
             .add(popups::PopupPlugin)
             .add(resource_deposit::ResourceDepositPlugin)
             .add(health::HealthPlugin)
+            .add(rubble::RubblePlugin)
             .add(tilemap::TilemapPlugin)
             .add(camera::CameraPlugin)
             .add(shop::ShopPlugin)
-            .add(laser_visuals::LaserVisualPlugin)
+            .add(controls::ControlsPlugin)
+            .add(laser_visuals::LaserVisualPlugin::default())
+            .add(territory_visuals::TerritoryVisualsPlugin)
     }
 }
 
@@ -45,6 +55,11 @@ impl PluginGroup for PewPewBoomBuildingsPlugins {
             .add(amplifier::AmplifierPlugin)
             .add(refractor::RefractorPlugin)
             .add(reflector::ReflectorPlugin)
+            .add(y_reflector::YReflectorPlugin)
             .add(rotater::RotaterPlugin)
+            .add(splitter::SplitterPlugin)
+            .add(shield::ShieldPlugin)
+            .add(portal::PortalPlugin)
+            .add(capacitor::CapacitorPlugin)
     }
 }
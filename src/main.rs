@@ -4,13 +4,15 @@ use bevy::{
 };
 
 use pewpewboom::{
-    game_loop::GameInstanceBundle, tilemap::Tilemap, PewPewBoomBuildingsPlugins, PewPewBoomPlugins,
+    events::EventsPlugin, game_loop::GameInstanceBundle, tilemap::Tilemap,
+    PewPewBoomBuildingsPlugins, PewPewBoomPlugins,
 };
 
 fn main() {
     let mut app = App::new();
     app.add_plugins(DefaultPlugins);
     app.add_plugins((PewPewBoomPlugins, PewPewBoomBuildingsPlugins));
+    app.add_plugins(EventsPlugin::default());
     app.add_systems(Startup, spawn_camera);
     app.run();
 }